@@ -0,0 +1,123 @@
+//! 缓存词条的存储格式基准：旧的 JSON 与二进制格式的解码及数据库查询耗时
+//!
+//! 运行：cargo bench --bench codec
+
+// kd 只有二进制 crate，这里直接引入被测的源文件
+#![allow(dead_code)]
+
+#[path = "../src/infrastructure/storage/codec.rs"]
+mod codec;
+#[path = "../src/domain/model.rs"]
+pub mod model;
+
+/// codec 按 crate::domain::model 引用词条类型
+mod domain {
+    pub use super::model;
+}
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use model::{CollinsDisplayItem, QueryResult, QuerySource};
+use std::hint::black_box;
+
+const ROWS: usize = 5000;
+
+/// 一条带柯林斯释义和例句的典型词条
+fn sample(word: &str) -> QueryResult {
+    let mut result = QueryResult::new(word.to_string(), false);
+    result.found = true;
+    result.source = QuerySource::OfflineDb;
+    result.pronunciation_us = Some(format!("/{}/", word));
+    result.pronunciation_uk = Some(format!("/{}/", word));
+    result.translations = vec![
+        format!("n. {} 的释义", word),
+        "v. 示例动词释义".to_string(),
+        "adj. 示例形容词释义".to_string(),
+    ];
+    result.examples = (0..3)
+        .map(|i| {
+            (
+                format!("Example sentence {} with {}.", i, word),
+                format!("包含 {} 的例句 {}。", word, i),
+            )
+        })
+        .collect();
+    result.collins_items = (0..4)
+        .map(|i| CollinsDisplayItem {
+            additional: Some("(N-COUNT)".to_string()),
+            major_trans: Some(format!(
+                "释义 {}：A {} is something used in example {}.",
+                i, word, i
+            )),
+            examples: vec![(
+                format!("She used the {} again.", word),
+                format!("她又用了那个 {}。", word),
+            )],
+        })
+        .collect();
+    result.collins_rank = Some("CET4 TEM4".to_string());
+    result
+}
+
+/// 旧格式：zstd 压缩的 JSON
+fn encode_json(result: &QueryResult) -> Vec<u8> {
+    zstd::encode_all(&serde_json::to_vec(result).unwrap()[..], 0).unwrap()
+}
+
+fn encode_binary(result: &QueryResult) -> Vec<u8> {
+    codec::encode(result).unwrap().data
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let result = sample("example");
+    let mut group = c.benchmark_group("decode");
+    for (format, blob) in [
+        ("json", encode_json(&result)),
+        ("binary", encode_binary(&result)),
+    ] {
+        group.bench_with_input(BenchmarkId::from_parameter(format), &blob, |b, blob| {
+            b.iter(|| codec::decode(black_box(blob)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    for (format, encode) in [
+        ("json", encode_json as fn(&QueryResult) -> Vec<u8>),
+        ("binary", encode_binary),
+    ] {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE cache (ns TEXT NOT NULL, query TEXT NOT NULL, data BLOB NOT NULL, PRIMARY KEY (ns, query))",
+            [],
+        )
+        .unwrap();
+        for i in 0..ROWS {
+            let word = format!("word{}", i);
+            conn.execute(
+                "INSERT INTO cache (ns, query, data) VALUES ('en', ?, ?)",
+                rusqlite::params![word, encode(&sample(&word))],
+            )
+            .unwrap();
+        }
+        let mut stmt = conn
+            .prepare("SELECT data FROM cache WHERE ns = 'en' AND query = ?")
+            .unwrap();
+
+        let mut i = 0;
+        group.bench_function(BenchmarkId::from_parameter(format), |b| {
+            b.iter(|| {
+                i = (i + 7919) % ROWS;
+                let data: Vec<u8> = stmt
+                    .query_row([format!("word{}", i)], |row| row.get(0))
+                    .unwrap();
+                codec::decode(&data).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode, bench_lookup);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+// Batch lookup: run query_word over a word list with bounded concurrency
+use crate::application::query::query_word;
+use crate::domain::model::QueryResult;
+use crate::state::AppState;
+use futures_util::stream::{self, Stream, StreamExt};
+use std::collections::BTreeMap;
+
+/// Outcome of looking up one word of the batch
+pub struct BatchItem {
+    pub query: String,
+    pub outcome: Result<QueryResult, String>,
+}
+
+/// Parse a word list: one entry per line, blank lines and `#` comments skipped
+pub fn parse_word_list(input: &str) -> Vec<String> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Look up every word, at most `jobs` at a time, yielding items in input order
+pub fn lookup_all(
+    state: &AppState,
+    words: Vec<String>,
+    jobs: usize,
+    no_cache: bool,
+) -> impl Stream<Item = BatchItem> + '_ {
+    stream::iter(words)
+        .map(move |query| async move {
+            let outcome = query_word(state, &query, no_cache, false)
+                .await
+                .map_err(|e| e.to_string());
+            BatchItem { query, outcome }
+        })
+        .buffered(jobs.max(1))
+}
+
+/// Found/not-found/error counts and which source answered
+#[derive(Default)]
+pub struct BatchSummary {
+    pub total: usize,
+    pub found: usize,
+    pub not_found: usize,
+    pub errors: usize,
+    pub by_source: BTreeMap<String, usize>,
+}
+
+impl BatchSummary {
+    pub fn record(&mut self, item: &BatchItem) {
+        self.total += 1;
+        match &item.outcome {
+            Ok(result) => {
+                if result.found {
+                    self.found += 1;
+                    *self
+                        .by_source
+                        .entry(result.source.name().to_string())
+                        .or_default() += 1;
+                } else {
+                    self.not_found += 1;
+                }
+            }
+            Err(_) => self.errors += 1,
+        }
+    }
+}
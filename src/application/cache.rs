@@ -0,0 +1,103 @@
+// Cache maintenance: expiring and inspecting stored results
+use crate::domain::error::KdError;
+use crate::domain::model::{CacheEntryInfo, CacheStats, DictionaryReport, QueryResult};
+use crate::infrastructure::storage::db::{source_language, EN_NAMESPACE, OFFLINE_SOURCE};
+use crate::infrastructure::storage::dictionary;
+use crate::infrastructure::storage::maintenance::{self, CacheFilter};
+use crate::state::AppState;
+use chrono::Utc;
+use std::time::Duration;
+
+/// Entries matching `filter`, most recently updated first
+pub async fn list(
+    state: &AppState,
+    filter: &CacheFilter,
+    limit: usize,
+) -> Result<Vec<CacheEntryInfo>, KdError> {
+    maintenance::list_entries(&state.db, filter, limit).await
+}
+
+/// Stored entries for `word`, looked up under the same key `kd <word>` uses
+pub async fn show(
+    state: &AppState,
+    word: &str,
+    ns: Option<&str>,
+) -> Result<Vec<(CacheEntryInfo, QueryResult)>, KdError> {
+    let key = cache_key(state, word, ns).await;
+    let entries = maintenance::find_entries(&state.db, &key, ns).await?;
+    // Long text is stored with its case kept
+    if entries.is_empty() && key != word.trim() {
+        return maintenance::find_entries(&state.db, word.trim(), ns).await;
+    }
+    Ok(entries)
+}
+
+/// Delete entries by word or GLOB pattern; returns how many were removed
+///
+/// A word is normalized like `kd <word>` does; a pattern is matched as given,
+/// since case folding would change classes such as `[A-Z]`. Offline dictionary
+/// rows are only deleted when `include_offline` is set.
+pub async fn remove(
+    state: &AppState,
+    pattern: &str,
+    filter: &CacheFilter,
+    include_offline: bool,
+) -> Result<usize, KdError> {
+    let key = if maintenance::is_glob(pattern) {
+        pattern.to_string()
+    } else {
+        cache_key(state, pattern, filter.ns.as_deref()).await
+    };
+
+    if !include_offline && filter.source.as_deref().is_none_or(|s| s == OFFLINE_SOURCE) {
+        let offline = CacheFilter {
+            source: Some(OFFLINE_SOURCE.to_string()),
+            ..filter.clone()
+        };
+        let count = maintenance::count_entries(&state.db, &key, &offline).await?;
+        if count > 0 {
+            return Err(KdError::Unconfirmed(format!(
+                "{} offline dictionary entries match \"{}\"; pass --yes to delete them",
+                count, pattern
+            )));
+        }
+    }
+
+    let removed = maintenance::remove_entries(&state.db, &key, filter).await?;
+    state.cache.clear();
+    Ok(removed)
+}
+
+/// Delete online results not refreshed within `older_than`; returns how many were removed
+pub async fn prune(state: &AppState, older_than: Duration) -> Result<usize, KdError> {
+    let cutoff = Utc::now().timestamp() - older_than.as_secs() as i64;
+    let pruned = maintenance::prune_cache(&state.db, cutoff).await?;
+    // Pruned rows may still be in memory in long-running modes
+    state.cache.clear();
+    Ok(pruned)
+}
+
+/// Compact the database; returns the file size in bytes before and after
+pub async fn vacuum(state: &AppState) -> Result<(u64, u64), KdError> {
+    maintenance::vacuum(&state.db).await
+}
+
+pub async fn stats(state: &AppState) -> Result<CacheStats, KdError> {
+    maintenance::cache_stats(&state.db).await
+}
+
+/// Train a zstd dictionary on stored entries and recompress the cache with it
+pub async fn train_dictionary(
+    state: &AppState,
+    samples: usize,
+    max_size: usize,
+) -> Result<DictionaryReport, KdError> {
+    dictionary::train(&state.db, samples, max_size).await
+}
+
+/// Key of `word` in `ns` (English when unset), as `kd <word>` would store it
+async fn cache_key(state: &AppState, word: &str, ns: Option<&str>) -> String {
+    let lang = source_language(ns.unwrap_or(EN_NAMESPACE));
+    let config = state.config.read().await;
+    config.normalization.for_source(lang).normalize(word)
+}
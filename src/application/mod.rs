@@ -1,4 +1,7 @@
 // Application layer: Business logic and use cases
 
+pub mod batch;
+pub mod cache;
 pub mod query;
+pub mod search;
 pub mod update;
@@ -1,9 +1,19 @@
+use crate::domain::chinese::parse_pinyin;
 use crate::domain::error::KdError;
-use crate::domain::model::{QueryResult, QuerySource};
-use crate::infrastructure::network::client::query_youdao;
-use crate::infrastructure::storage::db::{insert_cache, query_cache};
+use crate::domain::language;
+use crate::domain::model::{LanguagePair, QueryResult, QuerySource, TranslateRequest};
+use crate::domain::morphology::lemma_candidates;
+use crate::domain::normalize::NormalizeOptions;
+use crate::domain::spelling::{tolerance_for, BkTree};
+use crate::infrastructure::network::provider::ProviderChain;
+use crate::infrastructure::storage::db::{
+    all_headwords, has_entry, insert_cache, namespace_for, query_cache, spelling_candidates,
+    EN_NAMESPACE, ZH_NAMESPACE,
+};
+use crate::infrastructure::storage::pinyin::{ensure_pinyin_index, lookup_pinyin};
 use crate::state::AppState;
 use chrono::Utc;
+use std::sync::RwLock as SyncRwLock;
 
 pub async fn query_word(
     state: &AppState,
@@ -11,10 +21,73 @@ pub async fn query_word(
     no_cache: bool,
     is_long_text: bool,
 ) -> Result<QueryResult, KdError> {
+    // Look up and store under the normalized key; show the query as typed
+    let (key, exact, pair, pinyin) = {
+        let config = state.config.read().await;
+        // Pinyin resolves to a Chinese headword, so the lookup is Chinese → English
+        let pinyin = config
+            .source_lang
+            .trim()
+            .eq_ignore_ascii_case(language::PINYIN);
+        let from = if pinyin {
+            "zh"
+        } else {
+            config.source_lang.as_str()
+        };
+        let pair = language::resolve_pair(query, from, &config.target_lang)?;
+        let options = if is_long_text {
+            config.normalization.for_long_text()
+        } else {
+            config.normalization.for_source(&pair.from)
+        };
+        let key = options.normalize(query);
+        let exact = NormalizeOptions {
+            fold_case: false,
+            ..options
+        }
+        .normalize(query);
+        (key, exact, pair, pinyin)
+    };
+    // A dictionary headword kept in its own case ("US" next to "us") is found as typed
+    let key = if exact != key && has_entry(&state.db, &namespace_for(&pair), &exact).await? {
+        exact
+    } else {
+        key
+    };
+    let mut result = if pinyin && !is_long_text {
+        match pinyin_entry(state, &key).await? {
+            Some(result) => result,
+            None => {
+                let mut result = QueryResult::new(key.clone(), false);
+                result.source = QuerySource::OfflineDb;
+                result
+            }
+        }
+    } else {
+        lookup(state, &key, &pair, no_cache, is_long_text).await?
+    };
+    result.query = query.trim().to_string();
+    result.detected_lang = language::detect(query).map(str::to_string);
+    result.lang_pair = Some(pair);
+
+    Ok(result)
+}
+
+async fn lookup(
+    state: &AppState,
+    query: &str,
+    pair: &LanguagePair,
+    no_cache: bool,
+    is_long_text: bool,
+) -> Result<QueryResult, KdError> {
+    let ns = namespace_for(pair);
+    let ns = ns.as_str();
+    let memory_key = memory_key(ns, query);
+
     // 1. Memory Cache
     if !no_cache {
-        if let Some(cached) = state.cache.get(query) {
-            let mut res = cached.clone();
+        if let Some(mut res) = state.cache.get(&memory_key) {
+            revalidate(state, &mut res, query, pair, is_long_text);
             res.source = QuerySource::LocalCache;
             return Ok(res);
         }
@@ -22,48 +95,266 @@ pub async fn query_word(
 
     // 2. Database Cache
     if !no_cache {
-        if let Some(cached) = query_cache(&state.db, query).await? {
+        if let Some(mut res) = offline_entry(state, ns, query).await? {
             // Update memory cache
-            state.cache.insert(query.to_string(), cached.clone());
-
-            let mut res = cached;
-            // Keep original source if it was from online, otherwise mark as offline
-            if matches!(res.source, QuerySource::Online(_)) {
-                // Keep online source for display
-            } else {
-                res.source = QuerySource::OfflineDb;
-            }
+            state.cache.insert(memory_key, res.clone());
+            revalidate(state, &mut res, query, pair, is_long_text);
             return Ok(res);
         }
     }
 
     // 3. Online Query
-    // Use a read lock for config, but don't hold it across await if possible or safe
-    let mut result = {
+    let online = match fetch_online(state, query, pair, is_long_text).await {
+        Ok(mut result) if result.found => {
+            // 4. Write back to cache
+            if !no_cache {
+                store(state, query, pair, &mut result).await?;
+            }
+            return Ok(result);
+        }
+        online => online,
+    };
+
+    // 5. Guesses, only when nothing knows the word itself: the lemma of an
+    // inflected form, then the query read as pinyin. Not memory-cached, so the
+    // word is asked online again next time.
+    if !no_cache && !is_long_text && ns == EN_NAMESPACE {
+        if let Some(res) = lemma_entry(state, ns, query).await? {
+            return Ok(res);
+        }
+        if let Some(res) = pinyin_entry(state, query).await? {
+            return Ok(res);
+        }
+    }
+
+    match online {
+        Ok(mut result) => {
+            result.suggestions = suggest(state, query, is_long_text).await;
+            Ok(result)
+        }
+        Err(e) => {
+            // Online is down or unconfigured: close offline matches beat a bare error
+            let suggestions = suggest(state, query, is_long_text).await;
+            if suggestions.is_empty() {
+                return Err(e);
+            }
+            tracing::debug!("Online lookup failed, offering suggestions: {}", e);
+            let mut result = QueryResult::new(query.to_string(), is_long_text);
+            result.source = QuerySource::Suggestions;
+            result.suggestions = suggestions;
+            Ok(result)
+        }
+    }
+}
+
+/// Memory cache entries are per namespace, like the rows they mirror
+fn memory_key(ns: &str, query: &str) -> String {
+    format!("{}:{}", ns, query)
+}
+
+/// Ask the online providers, in the configured order
+async fn fetch_online(
+    state: &AppState,
+    query: &str,
+    pair: &LanguagePair,
+    is_long_text: bool,
+) -> Result<QueryResult, KdError> {
+    // Build the provider chain from a config snapshot so the lock isn't held across requests
+    let chain = {
         let config = state.config.read().await;
-        query_youdao(&state.http_client, &config, query).await?
+        if is_long_text {
+            ProviderChain::for_long_text(&state.http_client, &config)
+        } else {
+            ProviderChain::from_config(&state.http_client, &config)
+        }
     };
+    let request = TranslateRequest::new(query.to_string(), pair, is_long_text);
+    let mut result = chain.translate(&request).await?;
 
     // If needed, update is_long_text if not set by API
     if is_long_text {
         result.is_long_text = true;
     }
+    Ok(result)
+}
+
+/// Write an online result to the memory and database caches
+async fn store(
+    state: &AppState,
+    query: &str,
+    pair: &LanguagePair,
+    result: &mut QueryResult,
+) -> Result<(), KdError> {
+    let ns = namespace_for(pair);
+    result.cached_at = Some(Utc::now().timestamp());
+    state.cache.insert(memory_key(&ns, query), result.clone());
+    insert_cache(&state.db, &ns, query, result).await?;
+    index_headword(state, &ns, query);
+    Ok(())
+}
+
+/// Serve an expired online result as stale and refresh it in the background
+///
+/// Offline dictionary entries never expire; online results expire after the
+/// TTL configured for the provider that produced them.
+fn revalidate(
+    state: &AppState,
+    result: &mut QueryResult,
+    query: &str,
+    pair: &LanguagePair,
+    is_long_text: bool,
+) {
+    let (QuerySource::Online(source), Some(cached_at)) = (&result.source, result.cached_at) else {
+        return;
+    };
+    let Some(ttl) = state.ttl.for_source(source.name()) else {
+        return;
+    };
+    if Utc::now().timestamp() - cached_at < ttl.as_secs() as i64 {
+        return;
+    }
+    result.stale = true;
 
-    // 4. Write back to cache
-    // Only cache if found
-    if !no_cache && result.found {
-        result.cached_at = Some(Utc::now().timestamp());
+    let background = state.clone();
+    let query = query.to_string();
+    let pair = pair.clone();
+    let key = memory_key(&namespace_for(&pair), &query);
+    state.spawn_refresh(key, async move {
+        match fetch_online(&background, &query, &pair, is_long_text).await {
+            Ok(mut fresh) if fresh.found => {
+                if let Err(e) = store(&background, &query, &pair, &mut fresh).await {
+                    tracing::warn!("Failed to store refreshed {:?}: {}", query, e);
+                }
+            }
+            // Keep serving the old result rather than replacing it with a miss
+            Ok(_) => tracing::debug!("Refresh of {:?} found nothing", query),
+            Err(e) => tracing::warn!("Background refresh of {:?} failed: {}", query, e),
+        }
+    });
+}
 
-        // Update memory cache
-        state.cache.insert(query.to_string(), result.clone());
+/// Offline entry for `key`, labelled as offline unless it came from an online provider
+async fn offline_entry(
+    state: &AppState,
+    ns: &str,
+    key: &str,
+) -> Result<Option<QueryResult>, KdError> {
+    let Some(mut res) = query_cache(&state.db, ns, key).await? else {
+        return Ok(None);
+    };
+    // Keep original source if it was from online, otherwise mark as offline
+    if !matches!(res.source, QuerySource::Online(_)) {
+        res.source = QuerySource::OfflineDb;
+    }
+    Ok(Some(res))
+}
 
-        // Update DB cache
-        let db_result = result.clone();
-        let db = state.db.clone();
-        let q = query.to_string();
+/// Offline entry of the first lemma candidate of an inflected `query`
+async fn lemma_entry(
+    state: &AppState,
+    ns: &str,
+    query: &str,
+) -> Result<Option<QueryResult>, KdError> {
+    for lemma in lemma_candidates(query) {
+        if let Some(mut res) = offline_entry(state, ns, &lemma).await? {
+            res.query = query.to_string();
+            res.headword = Some(lemma);
+            return Ok(Some(res));
+        }
+    }
+    Ok(None)
+}
+
+/// Treat `query` as pinyin and look up the best matching Chinese headword
+async fn pinyin_entry(state: &AppState, query: &str) -> Result<Option<QueryResult>, KdError> {
+    let Some(key) = parse_pinyin(query) else {
+        return Ok(None);
+    };
+    let limit = state.config.read().await.max_suggestions;
 
-        insert_cache(&db, &q, &db_result).await?;
+    let indexed = ensure_pinyin_index(&state.db).await?;
+    if indexed > 0 {
+        tracing::info!("Built pinyin index for {} headwords", indexed);
     }
 
-    Ok(result)
+    let mut candidates = lookup_pinyin(&state.db, &key, limit + 1).await?.into_iter();
+    for headword in candidates.by_ref() {
+        if let Some(mut res) = offline_entry(state, ZH_NAMESPACE, &headword).await? {
+            res.query = query.to_string();
+            res.headword = Some(headword);
+            res.suggestions = candidates.collect();
+            return Ok(Some(res));
+        }
+    }
+    Ok(None)
+}
+
+/// Closest offline headwords to a word that wasn't found
+async fn suggest(state: &AppState, query: &str, is_long_text: bool) -> Vec<String> {
+    let limit = state.config.read().await.max_suggestions;
+    // Edit distance is only meaningful for single English words and short phrases
+    if limit == 0 || is_long_text || query.len() > 40 || !query.is_ascii() {
+        return Vec::new();
+    }
+
+    let tolerance = tolerance_for(query);
+    let words = match &state.suggestions {
+        Some(index) => match index
+            .get_or_try_init(|| build_suggestion_index(state))
+            .await
+        {
+            Ok(tree) => {
+                Ok(tree
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .find(query, tolerance, limit + 1))
+            }
+            Err(e) => Err(e),
+        },
+        // A single lookup can't amortize a full index; search the likely headwords only
+        None => spelling_candidates(&state.db, EN_NAMESPACE, query, tolerance)
+            .await
+            .map(|words| {
+                let mut tree = BkTree::new();
+                words.into_iter().for_each(|word| tree.insert(word));
+                tree.find(query, tolerance, limit + 1)
+            }),
+    };
+    match words {
+        // With --nocache the word itself may be a headword; don't suggest it back
+        Ok(words) => words
+            .into_iter()
+            .filter(|word| word != query)
+            .take(limit)
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to look up suggestions: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+async fn build_suggestion_index(state: &AppState) -> Result<SyncRwLock<BkTree>, KdError> {
+    let words = all_headwords(&state.db, EN_NAMESPACE).await?;
+    tokio::task::spawn_blocking(move || {
+        let mut tree = BkTree::new();
+        for word in words.into_iter().filter(|w| w.is_ascii()) {
+            tree.insert(word);
+        }
+        SyncRwLock::new(tree)
+    })
+    .await
+    .map_err(|e| KdError::Io(std::io::Error::other(format!("Task join error: {}", e))))
+}
+
+/// Add a newly stored headword to the spelling index, if one was built
+fn index_headword(state: &AppState, ns: &str, query: &str) {
+    let Some(tree) = state.suggestions.as_ref().and_then(|index| index.get()) else {
+        return;
+    };
+    if ns == EN_NAMESPACE && query.is_ascii() {
+        tree.write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(query.to_string());
+    }
 }
@@ -0,0 +1,19 @@
+// Search use case: full-text, prefix and wildcard lookup over the offline data
+use crate::domain::error::KdError;
+use crate::domain::model::SearchHit;
+use crate::infrastructure::storage::search::{ensure_search_index, search_cache};
+use crate::state::AppState;
+
+pub async fn search_entries(
+    state: &AppState,
+    pattern: &str,
+    limit: usize,
+) -> Result<Vec<SearchHit>, KdError> {
+    // Dictionaries imported before the index existed are indexed on first search
+    let indexed = ensure_search_index(&state.db).await?;
+    if indexed > 0 {
+        tracing::info!("Built search index for {} entries", indexed);
+    }
+
+    search_cache(&state.db, pattern, limit).await
+}
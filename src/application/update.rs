@@ -1,87 +1,55 @@
 use crate::domain::error::KdError;
 use crate::domain::model::QueryResult;
-use crate::infrastructure::storage::db::batch_insert_cache;
+use crate::domain::normalize::NormalizeOptions;
+use crate::infrastructure::config::{MirrorConfig, UpdateConfig};
+use crate::infrastructure::network::manifest::{self, Manifest};
+use crate::infrastructure::network::mirror;
+use crate::infrastructure::storage::codec::{self, EncodedEntry};
+use crate::infrastructure::storage::db::{
+    insert_encoded_batch, source_language, EN_NAMESPACE, ZH_NAMESPACE,
+};
 use crate::migration::legacy::LegacyResult;
 use crate::state::AppState;
 use flate2::read::ZlibDecoder;
 use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::Client;
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::{Client, StatusCode, Url};
 use rusqlite::Connection;
-use serde::Deserialize;
 use std::io::Read;
 use std::path::{Path, PathBuf};
-use tokio::fs::File;
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::fs::{File, OpenOptions};
 use tokio::io::AsyncWriteExt;
 
-const DATA_ZIP_URL_CN: &str = "https://gitee.com/void_kmz/kd/releases/download/v0.0.1/kd_data.zip";
-const DATA_ZIP_URL_GLOBAL: &str =
-    "https://raw.githubusercontent.com/Karmenzind/static/main/kd/kd_data.zip";
-
-#[derive(Debug, Deserialize)]
-struct IPInfo {
-    country: String,
-}
-
-impl IPInfo {
-    fn is_cn(&self) -> bool {
-        self.country.to_uppercase() == "CN"
-    }
-}
-
-/// Get download URL based on IP location
-async fn get_download_url(client: &Client) -> &'static str {
-    // Try to detect IP location, default to CN if detection fails
-    match detect_ip_location(client).await {
-        Ok(info) => {
-            if !info.is_cn() {
-                println!("Detected non-CN IP, using global download source.");
-                return DATA_ZIP_URL_GLOBAL;
-            }
-        }
-        Err(e) => {
-            println!(
-                "Failed to detect IP location ({}), using CN source as fallback.",
-                e
-            );
-        }
-    }
-    DATA_ZIP_URL_CN
-}
-
-/// Detect IP location using ipinfo.io
-async fn detect_ip_location(client: &Client) -> Result<IPInfo, KdError> {
-    let response = client
-        .get("https://ipinfo.io/json")
-        .timeout(std::time::Duration::from_secs(10))
-        .send()
-        .await?;
-
-    let info: IPInfo = response.json().await?;
-    Ok(info)
-}
-
-pub async fn update_dict(state: &AppState) -> Result<(), KdError> {
+/// Update the offline dictionary, downloading it or installing it from `from`
+pub async fn update_dict(state: &AppState, from: Option<&Path>) -> Result<(), KdError> {
     let config_guard = state.config.read().await;
     let db_path = crate::infrastructure::config::get_database_path(&config_guard);
     let data_dir = db_path.parent().unwrap();
+    let normalization = config_guard.normalization.clone();
+    let update_config = config_guard.update.clone();
     drop(config_guard);
 
-    let zip_path = data_dir.join("kd_data.zip");
-
-    // Auto download and extract
-    if !zip_path.exists() {
-        let download_url = get_download_url(&state.http_client).await;
-        let source_name = if download_url == DATA_ZIP_URL_CN {
-            "Gitee (CN)"
-        } else {
-            "GitHub (Global)"
-        };
-        println!("Downloading dictionary data from {}...", source_name);
-        download_file(&state.http_client, download_url, &zip_path).await?;
-    } else {
-        println!("Zip file already exists, skipping download.");
-    }
+    let zip_path = match from {
+        Some(path) => match resolve_local(&state.http_client, path, &update_config).await? {
+            LocalSource::Archive(archive) => archive,
+            LocalSource::Database(source_db_path) => {
+                // Nothing to extract, and the user's file is left alone
+                println!("Migrating data from {:?}...", source_db_path);
+                migrate_data(&source_db_path, &state.db, &normalization).await?;
+                println!("Dictionary update complete!");
+                return Ok(());
+            }
+        },
+        None => {
+            let zip_path = data_dir.join("kd_data.zip");
+            download_archive(state, &zip_path, &update_config).await?;
+            zip_path
+        }
+    };
 
     println!("Extracting...");
     extract_zip(&zip_path, data_dir).await?;
@@ -94,10 +62,10 @@ pub async fn update_dict(state: &AppState) -> Result<(), KdError> {
     println!("Found database file: {:?}", source_db_path);
 
     println!("Migrating data from {:?}...", source_db_path);
-    migrate_data(&source_db_path, &state.db).await?;
+    migrate_data(&source_db_path, &state.db, &normalization).await?;
 
-    // Cleanup
-    if zip_path.exists() {
+    // Cleanup (an archive given with --from is the user's to keep)
+    if from.is_none() && zip_path.exists() {
         tokio::fs::remove_file(&zip_path).await?;
         println!("Cleaned up zip file.");
     }
@@ -110,29 +78,382 @@ pub async fn update_dict(state: &AppState) -> Result<(), KdError> {
     Ok(())
 }
 
-async fn download_file(client: &Client, url: &str, path: &Path) -> Result<(), KdError> {
-    let res = client.get(url).send().await?;
-    let total_size = res.content_length().unwrap_or(0);
+/// Download the archive to `zip_path` from the first configured mirror that delivers it
+async fn download_archive(
+    state: &AppState,
+    zip_path: &Path,
+    update_config: &UpdateConfig,
+) -> Result<(), KdError> {
+    let mirrors = mirror::rank(
+        &state.http_client,
+        &update_config.mirrors(),
+        &update_config.selection,
+    )
+    .await?;
+
+    // Fall back to the next mirror whenever one can't deliver a good archive
+    let mut fetched = Err(KdError::Config(
+        "No mirror configured in [update]".to_string(),
+    ));
+    for mirror in &mirrors {
+        fetched = fetch_archive(&state.http_client, mirror, zip_path, update_config).await;
+        match &fetched {
+            Ok(_) => break,
+            Err(e) => eprintln!("Mirror {} failed: {}", mirror.display_name(), e),
+        }
+    }
+    if let Some(manifest) = fetched? {
+        println!("Verified dictionary data version {}.", manifest.version);
+    }
+    Ok(())
+}
+
+/// Dictionary data given with `--from`
+enum LocalSource {
+    Archive(PathBuf),
+    /// A legacy database, migrated as is
+    Database(PathBuf),
+}
+
+/// Work out what `--from` points at and check it against a manifest lying next to it, if any
+async fn resolve_local(
+    client: &Client,
+    path: &Path,
+    config: &UpdateConfig,
+) -> Result<LocalSource, KdError> {
+    let path = std::path::absolute(path)?;
+    let file = if path.is_dir() {
+        let found = match find_db_file(&path).await {
+            Some(db) => Some(db),
+            None => find_zip_file(&path).await,
+        };
+        found.ok_or_else(|| {
+            KdError::Io(std::io::Error::other(format!(
+                "No dictionary archive or DB file found in {}",
+                path.display()
+            )))
+        })?
+    } else {
+        path
+    };
+
+    // Files fetched by hand rarely come with a manifest: check against one only
+    // when it lies next to the file, or when a signed manifest is required
+    let signed = config.public_key.as_deref().is_some_and(|k| !k.is_empty());
+    if signed || with_suffix(&file, ".manifest.json").exists() {
+        let url = Url::from_file_path(&file)
+            .map_err(|_| KdError::Io(std::io::Error::other("Invalid dictionary file path")))?;
+        if let Some(manifest) = fetch_manifest(client, url.as_str(), config).await? {
+            manifest.verify_file(&file).await?;
+            println!("Verified dictionary data version {}.", manifest.version);
+        }
+    }
+
+    // Go by content rather than extension
+    let mut magic = [0u8; 16];
+    let read = std::fs::File::open(&file)?.read(&mut magic)?;
+    let magic = &magic[..read];
+    if magic.starts_with(b"SQLite format 3\0") {
+        println!("Found database file: {:?}", file);
+        Ok(LocalSource::Database(file))
+    } else if magic.starts_with(b"PK") {
+        check_existing_zip(&file, None).await?;
+        println!("Using archive {:?}", file);
+        Ok(LocalSource::Archive(file))
+    } else {
+        Err(KdError::Io(std::io::Error::other(format!(
+            "{} is neither a zip archive nor a SQLite database",
+            file.display()
+        ))))
+    }
+}
+
+/// Put the archive from `mirror` at `zip_path`, checked against its manifest if it has one
+async fn fetch_archive(
+    client: &Client,
+    mirror: &MirrorConfig,
+    zip_path: &Path,
+    config: &UpdateConfig,
+) -> Result<Option<Manifest>, KdError> {
+    // Fetch and check the manifest before trusting anything downloaded
+    let manifest = fetch_manifest(client, &mirror.url, config).await?;
+
+    // A previous run may have left an incomplete or corrupt archive behind
+    if zip_path.exists() {
+        match check_existing_zip(zip_path, manifest.as_ref()).await {
+            Ok(()) => {
+                println!("Zip file already exists, skipping download.");
+                return Ok(manifest);
+            }
+            Err(e) => {
+                println!("Existing zip file is unusable ({}), downloading again.", e);
+                tokio::fs::remove_file(zip_path).await?;
+            }
+        }
+    }
+
+    println!(
+        "Downloading dictionary data from {}...",
+        mirror.display_name()
+    );
+    download_file(client, &mirror.url, zip_path, config.retries).await?;
+
+    if let Some(manifest) = &manifest {
+        if let Err(e) = manifest.verify_file(zip_path).await {
+            // Don't leave the bad archive around to be picked up by the next update
+            tokio::fs::remove_file(zip_path).await?;
+            return Err(e);
+        }
+    }
+    Ok(manifest)
+}
+
+/// Fetch the manifest of the archive at `url`, checking its signature when a key is configured
+///
+/// Without a key, an archive that has no manifest is accepted with a warning.
+async fn fetch_manifest(
+    client: &Client,
+    url: &str,
+    config: &UpdateConfig,
+) -> Result<Option<Manifest>, KdError> {
+    let manifest = manifest::fetch(client, url).await?;
+    let public_key = config.public_key.as_deref().filter(|k| !k.is_empty());
+    match (&manifest, public_key) {
+        (Some(manifest), Some(public_key)) => manifest.verify_signature(public_key)?,
+        (None, Some(_)) => {
+            return Err(KdError::Integrity(format!(
+                "no manifest published at {}",
+                manifest::manifest_url(url)
+            )))
+        }
+        (None, None) => eprintln!(
+            "Warning: no manifest published for this source, the archive can't be verified."
+        ),
+        (Some(_), None) => {}
+    }
+    Ok(manifest)
+}
+
+/// Check that an archive left on disk is a complete zip matching the manifest
+async fn check_existing_zip(path: &Path, manifest: Option<&Manifest>) -> Result<(), KdError> {
+    let zip_path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<(), KdError> {
+        // A truncated zip is missing its central directory at the end
+        let file = std::fs::File::open(&zip_path)?;
+        zip::ZipArchive::new(file).map_err(|e| KdError::Io(std::io::Error::other(e)))?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| KdError::Io(std::io::Error::other(format!("Task join error: {}", e))))??;
+
+    match manifest {
+        Some(manifest) => manifest.verify_file(path).await,
+        None => Ok(()),
+    }
+}
+
+/// Download `url` to `path` through a `.part` file, resuming it after interruptions
+///
+/// Failed attempts are retried up to `retries` times with exponential backoff;
+/// the file only appears at `path` once it's complete. `file://` URLs are copied.
+async fn download_file(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    retries: u32,
+) -> Result<(), KdError> {
+    let part_path = with_suffix(path, ".part");
+
+    if let Some(source) = mirror::local_path(url) {
+        tokio::fs::copy(&source, &part_path).await?;
+        tokio::fs::rename(&part_path, path).await?;
+        return Ok(());
+    }
+
+    let mut attempt = 0;
+    loop {
+        match download_part(client, url, &part_path).await {
+            Ok(()) => break,
+            Err(e) if attempt < retries && is_retryable(&e) => {
+                let delay = RETRY_DELAY * 2u32.pow(attempt.min(5));
+                attempt += 1;
+                eprintln!(
+                    "Download interrupted ({}), retrying in {}s ({}/{})...",
+                    e,
+                    delay.as_secs(),
+                    attempt,
+                    retries
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    tokio::fs::rename(&part_path, path).await?;
+    tokio::fs::remove_file(with_suffix(&part_path, ".validator"))
+        .await
+        .ok();
+    Ok(())
+}
+
+/// `path` with `suffix` appended to its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Delay before the first retry of a download, doubled for each one after it (up to 32x)
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Network failures and server errors are worth retrying; other responses and disk errors won't change
+fn is_retryable(error: &KdError) -> bool {
+    match error {
+        KdError::Http(e) => e.status().is_none_or(|status| status.is_server_error()),
+        KdError::Io(e) => matches!(
+            e.kind(),
+            std::io::ErrorKind::UnexpectedEof
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::TimedOut
+        ),
+        _ => false,
+    }
+}
+
+/// Fetch whatever `part_path` is still missing, or all of it if it can't be resumed
+///
+/// The part is only resumed when the server's file is still the one it was
+/// started from: the ETag or Last-Modified of the first response is kept next
+/// to it and sent back as `If-Range`, and the `Content-Range` of the answer
+/// must start where the part ends. Otherwise the download starts over.
+async fn download_part(client: &Client, url: &str, part_path: &Path) -> Result<(), KdError> {
+    let validator_path = with_suffix(part_path, ".validator");
+    let mut resume = resume_point(part_path, &validator_path).await;
+
+    let res = loop {
+        let mut request = client.get(url);
+        if let Some((offset, validator)) = &resume {
+            request = request
+                .header(RANGE, format!("bytes={}-", offset))
+                .header(IF_RANGE, validator.as_str());
+        }
+        let res = request.send().await?;
+        let lines_up = match &resume {
+            None => true,
+            Some((offset, _)) => match res.status() {
+                StatusCode::RANGE_NOT_SATISFIABLE => false,
+                StatusCode::PARTIAL_CONTENT => content_range_start(&res) == Some(*offset),
+                // The whole file: it changed on the server, or the server can't resume
+                _ => true,
+            },
+        };
+        if lines_up {
+            break res;
+        }
+        println!("Partial download no longer matches the server's file, starting over.");
+        resume = None;
+    };
+    let res = res.error_for_status()?;
+
+    let resumed = resume.is_some() && res.status() == StatusCode::PARTIAL_CONTENT;
+    let (mut file, start) = match &resume {
+        Some((offset, _)) if resumed => {
+            println!("Resuming download at {} bytes.", offset);
+            let file = OpenOptions::new().append(true).open(part_path).await?;
+            (file, *offset)
+        }
+        _ => {
+            // Keep what identifies this version of the file for resuming it later
+            match validator(&res) {
+                Some(validator) => tokio::fs::write(&validator_path, validator).await?,
+                None => {
+                    if validator_path.exists() {
+                        tokio::fs::remove_file(&validator_path).await?;
+                    }
+                }
+            }
+            (File::create(part_path).await?, 0)
+        }
+    };
+    let expected = res.content_length();
 
-    let pb = ProgressBar::new(total_size);
+    let pb = ProgressBar::new(start + expected.unwrap_or(0));
     pb.set_style(ProgressStyle::default_bar()
         .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
         .unwrap()
         .progress_chars("#>-"));
+    pb.set_position(start);
 
-    let mut file = File::create(path).await?;
     let mut stream = res.bytes_stream();
-
-    while let Some(item) = stream.next().await {
-        let chunk = item.map_err(KdError::Http)?;
-        file.write_all(&chunk).await?;
-        pb.inc(chunk.len() as u64);
+    let mut received = 0;
+    let streamed: Result<(), KdError> = async {
+        while let Some(item) = stream.next().await {
+            let chunk = item.map_err(KdError::Http)?;
+            file.write_all(&chunk).await?;
+            received += chunk.len() as u64;
+            pb.inc(chunk.len() as u64);
+        }
+        Ok(())
+    }
+    .await;
+    // tokio finishes writes in the background; make sure they land before the
+    // file is read or resumed, including when the transfer broke off
+    file.flush().await?;
+    streamed?;
+
+    if let Some(expected) = expected {
+        if received < expected {
+            return Err(KdError::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("download ended after {} of {} bytes", received, expected),
+            )));
+        }
     }
 
     pb.finish_with_message("Downloaded");
     Ok(())
 }
 
+/// Where to resume `part_path` and the validator to send with `If-Range`
+///
+/// `None` when there's nothing to resume or nothing to tell whether the
+/// server's file is still the same.
+async fn resume_point(part_path: &Path, validator_path: &Path) -> Option<(u64, String)> {
+    let offset = tokio::fs::metadata(part_path).await.ok()?.len();
+    let validator = tokio::fs::read_to_string(validator_path).await.ok()?;
+    (offset > 0 && !validator.is_empty()).then_some((offset, validator))
+}
+
+/// A strong ETag or else the Last-Modified date, usable as `If-Range`
+fn validator(res: &reqwest::Response) -> Option<String> {
+    let header = |name| {
+        res.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    // If-Range only takes strong ETags
+    header(ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(LAST_MODIFIED))
+}
+
+/// First byte of a `Content-Range: bytes <start>-<end>/<total>` header
+fn content_range_start(res: &reqwest::Response) -> Option<u64> {
+    res.headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
 async fn extract_zip(zip_path: &Path, dest: &Path) -> Result<(), KdError> {
     // Use spawn_blocking for CPU-intensive zip extraction
     let zip_path = zip_path.to_path_buf();
@@ -170,6 +491,19 @@ async fn extract_zip(zip_path: &Path, dest: &Path) -> Result<(), KdError> {
     .map_err(|e| KdError::Io(std::io::Error::other(format!("Task join error: {}", e))))?
 }
 
+async fn find_zip_file(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let entries = std::fs::read_dir(&dir).ok()?;
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| path.extension().and_then(|s| s.to_str()) == Some("zip"))
+    })
+    .await
+    .ok()?
+}
+
 async fn find_db_file(dir: &Path) -> Option<PathBuf> {
     let dir = dir.to_path_buf();
     tokio::task::spawn_blocking(move || {
@@ -188,68 +522,42 @@ async fn find_db_file(dir: &Path) -> Option<PathBuf> {
     .ok()?
 }
 
-type MigrationRow = (String, Vec<u8>);
-type MigrationData = (i64, Vec<MigrationRow>);
+/// Legacy rows read from the source database per chunk
+const READ_CHUNK: usize = 1_000;
+/// Migrated rows written per transaction
+const WRITE_BATCH: usize = 10_000;
+/// Chunks buffered between two stages of the migration
+const CHANNEL_CAPACITY: usize = 8;
+
+/// A legacy headword and its (usually zlib-compressed) JSON detail
+type LegacyRow = (String, Vec<u8>);
+type MigratedRow = (String, QueryResult, EncodedEntry);
+
+/// A chunk of legacy rows after conversion
+struct ConvertedChunk {
+    rows: Vec<MigratedRow>,
+    /// Rows read from the source, including those that failed to convert
+    read: usize,
+    errors: usize,
+}
 
+/// Copy the legacy `en`/`ch` tables into the cache
+///
+/// Rows stream through three stages joined by bounded channels, so memory use
+/// doesn't grow with the size of the dictionary: a reader pulls chunks from the
+/// source table, worker threads decompress, convert and encode them, and the
+/// writer inserts the results in large transactions.
 async fn migrate_data(
     source_db_path: &Path,
     target_conn: &tokio_rusqlite::Connection,
+    normalization: &NormalizeOptions,
 ) -> Result<(), KdError> {
-    let source_db_path = source_db_path.to_path_buf();
-    let tables = vec!["en", "ch"];
-
-    for table in tables {
-        // Read all data from source database in a single blocking task
-        let table_clone = table.to_string();
-        let source_path = source_db_path.clone();
-        let (total, rows): MigrationData =
-            tokio::task::spawn_blocking(move || -> Result<MigrationData, KdError> {
-                let src_conn = Connection::open(&source_path).map_err(KdError::Sqlite)?;
-
-                // Check table existence
-                let table_exists: bool = src_conn
-                    .query_row(
-                        "SELECT exists(SELECT 1 FROM sqlite_master WHERE type='table' AND name=?)",
-                        [&table_clone],
-                        |row| row.get(0),
-                    )
-                    .unwrap_or(false);
-
-                if !table_exists {
-                    return Ok((0, Vec::new()));
-                }
+    // Source table and the namespace its headwords go to
+    let tables = vec![("en", EN_NAMESPACE), ("ch", ZH_NAMESPACE)];
 
-                // Get total count
-                let total: i64 = src_conn
-                    .query_row(
-                        &format!("SELECT count(*) FROM {}", table_clone),
-                        [],
-                        |row| row.get(0),
-                    )
-                    .unwrap_or(0);
-
-                // Read all rows
-                let mut stmt = src_conn
-                    .prepare(&format!("SELECT query, detail FROM {}", table_clone))
-                    .map_err(KdError::Sqlite)?;
-
-                let rows = stmt
-                    .query_map([], |row| {
-                        let query: String = row.get(0)?;
-                        let detail: Vec<u8> = row.get(1)?;
-                        Ok((query, detail))
-                    })
-                    .map_err(KdError::Sqlite)?;
-
-                let rows_vec = rows
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(KdError::Sqlite)?;
-                Ok((total, rows_vec))
-            })
-            .await
-            .map_err(|e| KdError::Io(std::io::Error::other(format!("Task join error: {}", e))))??;
-
-        if rows.is_empty() {
+    for (table, ns) in tables {
+        let total = count_rows(source_db_path, table).await?;
+        if total == 0 {
             println!("Table {} not found in source DB or empty, skipping.", table);
             continue;
         }
@@ -257,90 +565,201 @@ async fn migrate_data(
         println!("Table {} has {} records", table, total);
         println!("Starting migration...");
 
-        println!("Processing records...");
-        let pb = ProgressBar::new(total as u64);
+        let pb = ProgressBar::new(total);
         pb.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%) {msg}")
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%) {per_sec} {msg}")
             .unwrap()
             .progress_chars("#>-"));
         pb.set_message(format!("Migrating {}", table));
 
+        let (chunk_tx, chunk_rx) = std::sync::mpsc::sync_channel(CHANNEL_CAPACITY);
+        let (converted_tx, mut converted_rx) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+
+        let source_path = source_db_path.to_path_buf();
+        let reader =
+            tokio::task::spawn_blocking(move || read_legacy_rows(&source_path, table, chunk_tx));
+
+        let workers = std::thread::available_parallelism().map_or(4, |n| n.get());
+        let chunk_rx = Arc::new(Mutex::new(chunk_rx));
+        let normalization = normalization.for_source(source_language(ns));
+        let converters: Vec<_> = (0..workers)
+            .map(|_| {
+                let chunk_rx = chunk_rx.clone();
+                let converted_tx = converted_tx.clone();
+                let normalization = normalization.clone();
+                tokio::task::spawn_blocking(move || {
+                    convert_chunks(&chunk_rx, &converted_tx, &normalization)
+                })
+            })
+            .collect();
+        // The writer stops once every worker has dropped its sender
+        drop(converted_tx);
+
         let mut count = 0;
         let mut error_count = 0;
-        let mut batch = Vec::new();
-        const BATCH_SIZE: usize = 100;
-
-        for (i, (query, detail_bytes)) in rows.iter().enumerate() {
-            let mut decoder = ZlibDecoder::new(&detail_bytes[..]);
-            let mut decompressed = Vec::new();
-
-            let final_bytes = if decoder.read_to_end(&mut decompressed).is_ok() {
-                decompressed
-            } else {
-                detail_bytes.clone()
-            };
-
-            match serde_json::from_slice::<LegacyResult>(&final_bytes) {
-                Ok(legacy) => {
-                    let new_result = convert_legacy(legacy);
-                    batch.push((query.clone(), new_result));
-
-                    // Batch insert using transaction
-                    if batch.len() >= BATCH_SIZE {
-                        match batch_insert_cache(target_conn, std::mem::take(&mut batch)).await {
-                            Ok(inserted) => {
-                                count += inserted;
-                                pb.set_message(format!("Migrating {} - {} inserted", table, count));
-                            }
-                            Err(e) => {
-                                error_count += BATCH_SIZE;
-                                if error_count <= 10 {
-                                    eprintln!("Batch insert error: {}", e);
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(_) => {
-                    error_count += 1;
-                }
-            }
-
-            // Print status every 100 records
-            if i > 0 && i % 100 == 0 {
-                println!("Processed {} records, inserted {} so far...", i, count);
-            }
-
-            // Update progress bar
-            if i % 100 == 0 {
-                pb.set_position(i as u64);
+        let mut batch = Vec::with_capacity(WRITE_BATCH);
+        while let Some(converted) = converted_rx.recv().await {
+            error_count += converted.errors;
+            pb.inc(converted.read as u64);
+            batch.extend(converted.rows);
+
+            if batch.len() >= WRITE_BATCH {
+                write_batch(target_conn, ns, &mut batch, &mut count, &mut error_count).await;
+                pb.set_message(format!("Migrating {} - {} inserted", table, count));
             }
         }
+        write_batch(target_conn, ns, &mut batch, &mut count, &mut error_count).await;
 
-        // Insert remaining batch
-        if !batch.is_empty() {
-            match batch_insert_cache(target_conn, batch).await {
-                Ok(inserted) => count += inserted,
-                Err(e) => {
-                    eprintln!("Final batch insert error: {}", e);
-                    error_count += 1;
-                }
-            }
+        reader
+            .await
+            .map_err(|e| KdError::Io(std::io::Error::other(format!("Task join error: {}", e))))??;
+        for converter in converters {
+            converter.await.map_err(|e| {
+                KdError::Io(std::io::Error::other(format!("Task join error: {}", e)))
+            })?;
         }
 
+        let elapsed = pb.elapsed().as_secs_f64().max(0.001);
+        let rate = (count as f64 / elapsed) as u64;
         pb.finish_with_message(format!(
             "Table {} done. Inserted {} records, {} errors.",
             table, count, error_count
         ));
         println!(
-            "\nTable {} completed: {} records inserted, {} errors",
-            table, count, error_count
+            "\nTable {} completed: {} records inserted, {} errors ({} records/s)",
+            table, count, error_count, rate
         );
     }
 
     Ok(())
 }
 
+/// Number of rows in a legacy table, 0 when the table doesn't exist
+async fn count_rows(source_db_path: &Path, table: &'static str) -> Result<u64, KdError> {
+    let source_path = source_db_path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<u64, KdError> {
+        let src_conn = Connection::open(&source_path)?;
+
+        let table_exists: bool = src_conn
+            .query_row(
+                "SELECT exists(SELECT 1 FROM sqlite_master WHERE type='table' AND name=?)",
+                [table],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        if !table_exists {
+            return Ok(0);
+        }
+
+        let total: i64 = src_conn
+            .query_row(&format!("SELECT count(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap_or(0);
+        Ok(total as u64)
+    })
+    .await
+    .map_err(|e| KdError::Io(std::io::Error::other(format!("Task join error: {}", e))))?
+}
+
+/// Send the rows of a legacy table in chunks until they run out or nobody is listening
+fn read_legacy_rows(
+    source_path: &Path,
+    table: &str,
+    chunks: SyncSender<Vec<LegacyRow>>,
+) -> Result<(), KdError> {
+    let src_conn = Connection::open(source_path)?;
+    let mut stmt = src_conn.prepare(&format!("SELECT query, detail FROM {}", table))?;
+    let mut rows = stmt.query([])?;
+
+    let mut chunk = Vec::with_capacity(READ_CHUNK);
+    while let Some(row) = rows.next()? {
+        chunk.push((row.get(0)?, row.get(1)?));
+        if chunk.len() == READ_CHUNK {
+            let full = std::mem::replace(&mut chunk, Vec::with_capacity(READ_CHUNK));
+            if chunks.send(full).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    if !chunk.is_empty() {
+        chunks.send(chunk).ok();
+    }
+    Ok(())
+}
+
+/// Worker loop: decompress, convert and encode chunks until the reader is done
+fn convert_chunks(
+    chunks: &Mutex<Receiver<Vec<LegacyRow>>>,
+    converted: &tokio::sync::mpsc::Sender<ConvertedChunk>,
+    normalization: &NormalizeOptions,
+) {
+    loop {
+        // Hold the lock only while waiting for the next chunk
+        let chunk = match chunks.lock().unwrap_or_else(|e| e.into_inner()).recv() {
+            Ok(chunk) => chunk,
+            Err(_) => return,
+        };
+
+        let read = chunk.len();
+        let rows: Vec<_> = chunk
+            .into_iter()
+            .filter_map(|(query, detail)| {
+                let (result, encoded) = convert_row(&detail)?;
+                Some((normalization.normalize(&query), result, encoded))
+            })
+            .collect();
+        let errors = read - rows.len();
+        if converted
+            .blocking_send(ConvertedChunk { rows, read, errors })
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Decode one legacy detail blob into a result and its stored encoding
+fn convert_row(detail: &[u8]) -> Option<(QueryResult, EncodedEntry)> {
+    let mut decoder = ZlibDecoder::new(detail);
+    let mut decompressed = Vec::new();
+    let bytes = if decoder.read_to_end(&mut decompressed).is_ok() {
+        &decompressed[..]
+    } else {
+        detail
+    };
+
+    let legacy = serde_json::from_slice::<LegacyResult>(bytes).ok()?;
+    let result = convert_legacy(legacy);
+    let encoded = codec::encode(&result).ok()?;
+    Some((result, encoded))
+}
+
+/// Insert and empty the pending batch in one transaction
+async fn write_batch(
+    target_conn: &tokio_rusqlite::Connection,
+    ns: &str,
+    batch: &mut Vec<MigratedRow>,
+    count: &mut usize,
+    error_count: &mut usize,
+) {
+    if batch.is_empty() {
+        return;
+    }
+    let rows = std::mem::replace(batch, Vec::with_capacity(WRITE_BATCH));
+    let len = rows.len();
+    match insert_encoded_batch(target_conn, ns, rows).await {
+        Ok(inserted) => *count += inserted,
+        Err(e) => {
+            // Only the first failures are worth printing
+            if *error_count < 10 {
+                eprintln!("Batch insert error: {}", e);
+            }
+            *error_count += len;
+        }
+    }
+}
+
 fn convert_legacy(legacy: LegacyResult) -> QueryResult {
     let keyword = legacy.keyword.unwrap_or_else(|| "unknown".to_string());
     let mut result = QueryResult::new(keyword.clone(), false);
@@ -0,0 +1,132 @@
+// Chinese text helpers: script detection, traditional → simplified and pinyin keys
+use ::pinyin::ToPinyinMulti;
+
+/// Most readings combinations kept per headword; heteronyms multiply quickly
+const MAX_READINGS: usize = 16;
+
+/// Whether `c` is a CJK ideograph (Unified, Extension A or Compatibility block)
+pub fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+pub fn contains_cjk(text: &str) -> bool {
+    text.chars().any(is_cjk)
+}
+
+/// Convert traditional characters to simplified, leaving anything else as is
+pub fn to_simplified(text: &str) -> String {
+    if contains_cjk(text) {
+        fast2s::convert(text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Pinyin as it is indexed and searched
+///
+/// `plain` is the toneless syllables run together ("diannao"), with ü written
+/// as v. `tones` is the sequence of tone numbers 1-4 ("43"); neutral tones
+/// contribute nothing, so "māma" is ("mama", "1").
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinyinKey {
+    pub plain: String,
+    pub tones: String,
+}
+
+/// Every pinyin reading of a Chinese headword
+///
+/// Latin letters and digits inside the headword ("T恤", "卡拉OK") are kept as
+/// they are; other characters are skipped.
+pub fn pinyin_keys(headword: &str) -> Vec<PinyinKey> {
+    let mut keys = vec![PinyinKey {
+        plain: String::new(),
+        tones: String::new(),
+    }];
+
+    for c in headword.chars() {
+        let readings: Vec<(String, Option<char>)> = match c.to_pinyin_multi() {
+            Some(multi) => {
+                let mut readings = Vec::new();
+                for i in 0..multi.count() {
+                    let pinyin = multi.get(i);
+                    let reading = (
+                        pinyin.plain().replace('ü', "v"),
+                        pinyin
+                            .with_tone_num_end()
+                            .chars()
+                            .last()
+                            .filter(|d| matches!(d, '1'..='4')),
+                    );
+                    if !readings.contains(&reading) {
+                        readings.push(reading);
+                    }
+                }
+                readings
+            }
+            None if c.is_ascii_alphanumeric() => vec![(c.to_ascii_lowercase().to_string(), None)],
+            None => continue,
+        };
+
+        keys = keys
+            .iter()
+            .flat_map(|key| {
+                readings.iter().map(move |(plain, tone)| PinyinKey {
+                    plain: format!("{}{}", key.plain, plain),
+                    tones: tone.map_or(key.tones.clone(), |t| format!("{}{}", key.tones, t)),
+                })
+            })
+            .take(MAX_READINGS)
+            .collect();
+    }
+
+    keys.retain(|key| !key.plain.is_empty());
+    keys
+}
+
+/// Parse user input as pinyin: toneless ("diannao", "dian nao"), tone numbers
+/// ("dian4nao3") or tone marks ("diàn nǎo")
+///
+/// Returns `None` when the input can't be pinyin at all. Whether the letters
+/// form real syllables is left to the index lookup.
+pub fn parse_pinyin(input: &str) -> Option<PinyinKey> {
+    let mut plain = String::new();
+    let mut tones = String::new();
+
+    for c in input.chars() {
+        match c {
+            'a'..='z' => plain.push(c),
+            'A'..='Z' => plain.push(c.to_ascii_lowercase()),
+            'ü' | 'Ü' => plain.push('v'),
+            '1'..='4' if !plain.is_empty() => tones.push(c),
+            '0' | '5' if !plain.is_empty() => {}
+            ' ' | '\'' | '-' => {}
+            _ => {
+                let (base, tone) = tone_mark(c)?;
+                plain.push(base);
+                tones.push(tone);
+            }
+        }
+    }
+
+    if plain.len() < 2 {
+        return None;
+    }
+    Some(PinyinKey { plain, tones })
+}
+
+/// Split a tone-marked vowel into its base letter and tone number
+fn tone_mark(c: char) -> Option<(char, char)> {
+    const MARKS: [(char, [char; 4]); 6] = [
+        ('a', ['ā', 'á', 'ǎ', 'à']),
+        ('e', ['ē', 'é', 'ě', 'è']),
+        ('i', ['ī', 'í', 'ǐ', 'ì']),
+        ('o', ['ō', 'ó', 'ǒ', 'ò']),
+        ('u', ['ū', 'ú', 'ǔ', 'ù']),
+        ('v', ['ǖ', 'ǘ', 'ǚ', 'ǜ']),
+    ];
+    let c = c.to_lowercase().next()?;
+    MARKS.iter().find_map(|(base, marked)| {
+        let tone = marked.iter().position(|m| *m == c)?;
+        Some((*base, char::from(b'1' + tone as u8)))
+    })
+}
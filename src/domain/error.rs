@@ -21,19 +21,23 @@ pub enum KdError {
     Toml(#[from] toml::de::Error),
 
     #[error("Compression error: {0}")]
-    #[allow(dead_code)]
     Compression(String),
 
     #[error("Configuration error: {0}")]
     Config(String),
 
     #[error("Start-up error: {0}")]
-    #[allow(dead_code)]
     Init(String),
 
     #[error("API Error: {0}")]
     Api(String),
 
+    #[error("Confirmation required: {0}")]
+    Unconfirmed(String),
+
+    #[error("Integrity check failed: {0}")]
+    Integrity(String),
+
     #[error("Time error: {0}")]
     Time(#[from] std::time::SystemTimeError),
 }
@@ -0,0 +1,275 @@
+// Language detection: writing system first, then trigram profiles for Latin text
+use crate::domain::chinese::is_cjk;
+use crate::domain::error::KdError;
+use crate::domain::model::LanguagePair;
+
+/// Placeholder code meaning "detect the source" or "pick the usual target"
+pub const AUTO: &str = "auto";
+/// Source "language" reading the query as pinyin for a Chinese headword
+pub const PINYIN: &str = "pinyin";
+
+/// Latin-script languages told apart by trigram profiles
+///
+/// Each profile lists the language's most frequent trigrams, most frequent
+/// first; `_` marks a word boundary.
+const PROFILES: &[(&str, &str)] = &[
+    (
+        "en",
+        "_th the he_ _an and nd_ ing ng_ _in _of of_ _to to_ ion tio ati on_ ent \
+         _is is_ er_ ed_ es_ for _fo hat tha _wa _be _co re_ _it at_ _wh ere",
+    ),
+    (
+        "fr",
+        "_de de_ es_ _le le_ ent nt_ _la la_ _et et_ les _co on_ ion tio _pa que \
+         ue_ _qu ait _po _un une our _en en_ re_ _da dan ans _re men eme _pr \
+         ous _vo vou _ce ce_ _ne ne_ _su est _es st_ _au eu_ oi_",
+    ),
+    (
+        "de",
+        "en_ er_ _de der ch_ ich sch che die _di ie_ und _un nd_ ein _ei cht _da \
+         den in_ ine gen ung ng_ te_ _ge _zu ist _is _mi _be _wi",
+    ),
+    (
+        "es",
+        "_de de_ os_ _la la_ es_ _qu que ue_ _el el_ ent as_ _en en_ _co ión \
+         ado _lo los _se _pa _po ar_ con nte _es est _un _ci ción",
+    ),
+    (
+        "it",
+        "_di di_ che he_ _ch la_ _la to_ re_ one ell _de _co ent _il il_ are zio \
+         ion _in _pe per _no no_ _pr lla del ato _un _so _è_ gli",
+    ),
+    (
+        "pt",
+        "_de de_ os_ _qu que ue_ _co ão_ ção do_ da_ _do _da ent _pa es_ _em \
+         em_ _se men _na nte _um _pr com _es as_ ar_ par _nã",
+    ),
+];
+
+/// Accented letters that point towards a language, each worth a few trigrams
+const LETTERS: &[(&str, &str)] = &[
+    ("fr", "èêëîïôûçœ"),
+    ("de", "äöü"),
+    ("es", "áíóú"),
+    ("it", "òì"),
+    ("pt", "âêôáíóúç"),
+];
+
+/// Score added for each accented letter listed in `LETTERS`
+const LETTER_WEIGHT: usize = 20;
+
+/// How many times English's trigram score another Latin language must reach
+const CONFIDENCE_RATIO: usize = 2;
+
+/// Trigram score another Latin language must reach, about three telling words
+const MIN_CONFIDENT_SCORE: usize = 150;
+
+/// Latin text shorter than this many words is too short for trigrams
+const MIN_WORDS_FOR_NGRAMS: usize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Script {
+    Latin,
+    Han,
+    Kana,
+    Hangul,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Thai,
+    Devanagari,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Self> {
+        let script = match c {
+            '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' => Script::Kana,
+            '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+                Script::Hangul
+            }
+            '\u{0370}'..='\u{03FF}' => Script::Greek,
+            '\u{0400}'..='\u{04FF}' => Script::Cyrillic,
+            '\u{0590}'..='\u{05FF}' => Script::Hebrew,
+            '\u{0600}'..='\u{06FF}' => Script::Arabic,
+            '\u{0900}'..='\u{097F}' => Script::Devanagari,
+            '\u{0E00}'..='\u{0E7F}' => Script::Thai,
+            c if is_cjk(c) => Script::Han,
+            c if c.is_ascii_alphabetic() => Script::Latin,
+            '\u{00C0}'..='\u{024F}' if c.is_alphabetic() => Script::Latin,
+            _ => return None,
+        };
+        Some(script)
+    }
+
+    /// Language code for scripts that (practically) belong to one language
+    fn language(self) -> Option<&'static str> {
+        match self {
+            Script::Han => Some("zh"),
+            Script::Kana => Some("ja"),
+            Script::Hangul => Some("ko"),
+            Script::Cyrillic => Some("ru"),
+            Script::Greek => Some("el"),
+            Script::Arabic => Some("ar"),
+            Script::Hebrew => Some("he"),
+            Script::Thai => Some("th"),
+            Script::Devanagari => Some("hi"),
+            Script::Latin => None,
+        }
+    }
+}
+
+/// Outcome of detection for text that has letters
+#[derive(Clone, Copy)]
+enum Guess {
+    /// The language, clear enough to translate from
+    Sure(&'static str),
+    /// Latin text leaning towards another language without clearly being it
+    Unsure,
+}
+
+/// Detect the language of `text` as an ISO 639-1 code
+///
+/// Returns `None` when the text has no letters at all ("123", "?"). Latin
+/// text that isn't clearly another language counts as English.
+pub fn detect(text: &str) -> Option<&'static str> {
+    guess(text).map(|guess| match guess {
+        Guess::Sure(lang) => lang,
+        Guess::Unsure => "en",
+    })
+}
+
+fn guess(text: &str) -> Option<Guess> {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    for script in text.chars().filter_map(Script::of) {
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+
+    // Japanese mixes kanji with kana, and any kana at all settles it
+    if counts.iter().any(|(s, _)| *s == Script::Kana) {
+        return Some(Guess::Sure("ja"));
+    }
+    let (script, _) = counts.into_iter().max_by_key(|(_, count)| *count)?;
+    Some(match script.language() {
+        Some(lang) => Guess::Sure(lang),
+        None => guess_latin(text),
+    })
+}
+
+/// Pick among the Latin-script languages, English unless the text says otherwise
+fn guess_latin(text: &str) -> Guess {
+    let text = text.to_lowercase();
+
+    // Letters only one of the profiled languages uses
+    if text.contains('ß') {
+        return Guess::Sure("de");
+    }
+    if text.contains(['ñ', '¿', '¡']) {
+        return Guess::Sure("es");
+    }
+    if text.contains(['ã', 'õ']) {
+        return Guess::Sure("pt");
+    }
+
+    // A word or two ("naïve", "café", "e-mail") is looked up as English
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.len() < MIN_WORDS_FOR_NGRAMS {
+        return Guess::Sure("en");
+    }
+
+    let mut best = ("en", 0);
+    let mut english = 0;
+    for (lang, profile) in PROFILES {
+        let ranked: Vec<&str> = profile.split_whitespace().collect();
+        let mut score = 0;
+        for word in &words {
+            let padded: Vec<char> = format!("_{}_", word).chars().collect();
+            for trigram in padded.windows(3) {
+                let trigram: String = trigram.iter().collect();
+                if let Some(rank) = ranked.iter().position(|t| *t == trigram) {
+                    score += ranked.len() - rank;
+                }
+            }
+        }
+        if let Some((_, letters)) = LETTERS.iter().find(|(l, _)| l == lang) {
+            score += LETTER_WEIGHT * text.chars().filter(|c| letters.contains(*c)).count();
+        }
+        if *lang == "en" {
+            english = score;
+        }
+        if score > best.1 {
+            best = (lang, score);
+        }
+    }
+
+    // Short phrases borrow foreign words ("de facto standard", "coup de grace"),
+    // so another language has to win clearly before English is given up
+    if best.0 == "en" {
+        Guess::Sure("en")
+    } else if best.1 >= MIN_CONFIDENT_SCORE && best.1 >= english * CONFIDENCE_RATIO {
+        Guess::Sure(best.0)
+    } else {
+        Guess::Unsure
+    }
+}
+
+/// Resolve the languages a query is translated between
+///
+/// `from` and `to` are codes as configured or given on the command line.
+/// An automatic source is detected from the query, and left to the provider
+/// when detection isn't sure; an automatic target is English for Chinese and
+/// Chinese for everything else.
+pub fn resolve_pair(query: &str, from: &str, to: &str) -> Result<LanguagePair, KdError> {
+    let from = match canonical_code(from) {
+        Some(code) if code == AUTO => match guess(query) {
+            Some(Guess::Sure(lang)) => lang.to_string(),
+            Some(Guess::Unsure) | None => AUTO.to_string(),
+        },
+        Some(code) => code,
+        None => return Err(KdError::Config(format!("Unknown language: {}", from))),
+    };
+    let to = match canonical_code(to) {
+        Some(code) if code == AUTO => if from == "zh" { "en" } else { "zh" }.to_string(),
+        Some(code) => code,
+        None => return Err(KdError::Config(format!("Unknown language: {}", to))),
+    };
+    Ok(LanguagePair { from, to })
+}
+
+/// Normalize a language code as typed ("JP", "zh-Hans", "de-de") to the form kd
+/// uses: ISO 639-1, plus an upper-case region where it matters ("zh-TW")
+///
+/// Returns `None` for anything that doesn't look like a language code.
+pub fn canonical_code(code: &str) -> Option<String> {
+    let code = code.trim().to_lowercase().replace('_', "-");
+    let code = match code.as_str() {
+        "" | AUTO => return Some(AUTO.to_string()),
+        "cn" | "chs" | "zh-cn" | "zh-sg" | "zh-hans" => "zh",
+        "cht" | "zh-tw" | "zh-hk" | "zh-hant" => return Some("zh-TW".to_string()),
+        "jp" => "ja",
+        "kr" => "ko",
+        other => other,
+    };
+
+    let (language, region) = match code.split_once('-') {
+        Some((language, region)) => (language, Some(region)),
+        None => (code, None),
+    };
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    match region {
+        // Only Chinese needs its region; "de-de" is just German
+        Some(region) if region.len() == 2 && region.chars().all(|c| c.is_ascii_lowercase()) => {
+            Some(language.to_string())
+        }
+        Some(_) => None,
+        None => Some(language.to_string()),
+    }
+}
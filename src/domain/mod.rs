@@ -1,5 +1,10 @@
 // Domain layer: Core business models and types
 
+pub mod chinese;
 pub mod error;
+pub mod language;
 pub mod model;
+pub mod morphology;
+pub mod normalize;
+pub mod spelling;
 pub mod traits;
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // 词典查询结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,16 @@ pub struct QueryResult {
     pub collins_rank: Option<String>,           // 等级标识 (CET4 TEM4)
     pub source: QuerySource,
     pub cached_at: Option<i64>,
+    #[serde(default)]
+    pub suggestions: Vec<String>, // 未找到时的相近词
+    #[serde(default)]
+    pub headword: Option<String>, // 词形还原后实际命中的词条，如 running → run
+    #[serde(default)]
+    pub detected_lang: Option<String>, // 识别出的查询语言 (ISO 639-1)，如 en、zh、ja
+    #[serde(default)]
+    pub lang_pair: Option<LanguagePair>, // 实际使用的翻译方向
+    #[serde(default)]
+    pub stale: bool, // 缓存已过期，正在后台刷新
 }
 
 // Collins 词典显示条目
@@ -40,6 +51,38 @@ impl QueryResult {
             collins_rank: None,
             source: QuerySource::Online(OnlineSource::Youdao), // Default source
             cached_at: None,
+            suggestions: Vec::new(),
+            headword: None,
+            detected_lang: None,
+            lang_pair: None,
+            stale: false,
+        }
+    }
+}
+
+// 翻译语言对
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguagePair {
+    pub from: String, // 源语言 (ISO 639-1)，未识别时为 "auto"
+    pub to: String,   // 目标语言 (ISO 639-1)
+}
+
+// 在线翻译请求
+#[derive(Debug, Clone)]
+pub struct TranslateRequest {
+    pub query: String,
+    pub from: String, // 源语言 (ISO 639-1)，未识别时为 "auto"
+    pub to: String,   // 目标语言 (ISO 639-1)
+    pub is_long_text: bool,
+}
+
+impl TranslateRequest {
+    pub fn new(query: String, pair: &LanguagePair, is_long_text: bool) -> Self {
+        Self {
+            query,
+            from: pair.from.clone(),
+            to: pair.to.clone(),
+            is_long_text,
         }
     }
 }
@@ -50,6 +93,35 @@ pub enum QuerySource {
     OfflineDb,
     LocalCache,
     Online(OnlineSource),
+    Suggestions, // 未找到，结果只包含拼写建议
+}
+
+impl QuerySource {
+    /// Short label used when storing or reporting the source
+    pub fn name(&self) -> &str {
+        match self {
+            QuerySource::OfflineDb => "offline",
+            QuerySource::LocalCache => "cache",
+            QuerySource::Online(source) => source.name(),
+            QuerySource::Suggestions => "suggestions",
+        }
+    }
+}
+
+// 查询历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub query: String,
+    pub found: bool,
+    pub source: String,
+    pub queried_at: i64,
+}
+
+// 全文搜索命中的词条
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub query: String,
+    pub summary: String,
 }
 
 // 在线查询源
@@ -58,6 +130,22 @@ pub enum OnlineSource {
     Youdao,
     Bing,
     Google,
+    LibreTranslate,
+    /// A user-defined HTTP JSON template backend, identified by its name
+    Custom(String),
+}
+
+impl OnlineSource {
+    /// Provider name as used in the `providers` config list
+    pub fn name(&self) -> &str {
+        match self {
+            OnlineSource::Youdao => "youdao",
+            OnlineSource::Bing => "bing",
+            OnlineSource::Google => "google",
+            OnlineSource::LibreTranslate => "libretranslate",
+            OnlineSource::Custom(name) => name,
+        }
+    }
 }
 
 // 压缩缓存数据结构 (用于存储，保留用于未来优化)
@@ -68,3 +156,49 @@ pub struct CompressedCache {
     pub compressed_size: usize,
     pub original_size: usize,
 }
+
+// 缓存条目的元数据（kd cache ls/show）
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntryInfo {
+    pub ns: String,
+    pub query: String,
+    pub source: String, // offline 或在线查询源名称
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub compressed_size: i64,
+    pub original_size: i64,
+}
+
+// 缓存统计（kd cache stats）
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub compressed_size: i64,
+    pub original_size: i64,
+    pub by_source: BTreeMap<String, usize>,
+    pub by_namespace: BTreeMap<String, usize>,
+}
+
+// 训练 zstd 字典的结果（kd cache train-dict）
+#[derive(Debug, Clone, Serialize)]
+pub struct DictionaryReport {
+    pub version: i64,
+    pub dict_size: usize,
+    pub samples: usize,
+    pub entries: usize,         // 用新字典重新压缩的条目数
+    pub compressed_before: i64, // 重新压缩前的总大小
+    pub compressed_after: i64,  // 重新压缩后的总大小
+    pub original_size: i64,     // 未压缩的总大小
+}
+
+// 内存缓存统计（kd --status、守护进程与 HTTP 服务）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryCacheStats {
+    pub entries: usize,
+    pub bytes: usize,       // 估算的占用字节数
+    pub max_entries: usize, // 0 表示不限
+    pub max_bytes: usize,   // 0 表示不限
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
@@ -0,0 +1,303 @@
+// English morphology: map inflected forms back to candidate headwords
+
+/// Irregular inflections as (form, lemma)
+const IRREGULAR: &[(&str, &str)] = &[
+    // Verbs
+    ("am", "be"),
+    ("is", "be"),
+    ("are", "be"),
+    ("was", "be"),
+    ("were", "be"),
+    ("been", "be"),
+    ("being", "be"),
+    ("has", "have"),
+    ("had", "have"),
+    ("does", "do"),
+    ("did", "do"),
+    ("done", "do"),
+    ("went", "go"),
+    ("gone", "go"),
+    ("goes", "go"),
+    ("arose", "arise"),
+    ("arisen", "arise"),
+    ("ate", "eat"),
+    ("eaten", "eat"),
+    ("beaten", "beat"),
+    ("became", "become"),
+    ("began", "begin"),
+    ("begun", "begin"),
+    ("bent", "bend"),
+    ("bit", "bite"),
+    ("bitten", "bite"),
+    ("bled", "bleed"),
+    ("blew", "blow"),
+    ("blown", "blow"),
+    ("broke", "break"),
+    ("broken", "break"),
+    ("bred", "breed"),
+    ("brought", "bring"),
+    ("built", "build"),
+    ("burnt", "burn"),
+    ("bought", "buy"),
+    ("caught", "catch"),
+    ("chose", "choose"),
+    ("chosen", "choose"),
+    ("came", "come"),
+    ("crept", "creep"),
+    ("dealt", "deal"),
+    ("dug", "dig"),
+    ("drew", "draw"),
+    ("drawn", "draw"),
+    ("dreamt", "dream"),
+    ("drank", "drink"),
+    ("drunk", "drink"),
+    ("drove", "drive"),
+    ("driven", "drive"),
+    ("dwelt", "dwell"),
+    ("fell", "fall"),
+    ("fallen", "fall"),
+    ("fed", "feed"),
+    ("felt", "feel"),
+    ("fought", "fight"),
+    ("found", "find"),
+    ("fled", "flee"),
+    ("flew", "fly"),
+    ("flown", "fly"),
+    ("forbade", "forbid"),
+    ("forbidden", "forbid"),
+    ("forgot", "forget"),
+    ("forgotten", "forget"),
+    ("forgave", "forgive"),
+    ("forgiven", "forgive"),
+    ("froze", "freeze"),
+    ("frozen", "freeze"),
+    ("got", "get"),
+    ("gotten", "get"),
+    ("gave", "give"),
+    ("given", "give"),
+    ("ground", "grind"),
+    ("grew", "grow"),
+    ("grown", "grow"),
+    ("hung", "hang"),
+    ("heard", "hear"),
+    ("hid", "hide"),
+    ("hidden", "hide"),
+    ("held", "hold"),
+    ("kept", "keep"),
+    ("knelt", "kneel"),
+    ("knew", "know"),
+    ("known", "know"),
+    ("laid", "lay"),
+    ("led", "lead"),
+    ("leapt", "leap"),
+    ("learnt", "learn"),
+    ("left", "leave"),
+    ("lent", "lend"),
+    ("lay", "lie"),
+    ("lain", "lie"),
+    ("lit", "light"),
+    ("lost", "lose"),
+    ("made", "make"),
+    ("meant", "mean"),
+    ("met", "meet"),
+    ("mistook", "mistake"),
+    ("mistaken", "mistake"),
+    ("paid", "pay"),
+    ("proven", "prove"),
+    ("rode", "ride"),
+    ("ridden", "ride"),
+    ("rang", "ring"),
+    ("rung", "ring"),
+    ("rose", "rise"),
+    ("risen", "rise"),
+    ("ran", "run"),
+    ("said", "say"),
+    ("saw", "see"),
+    ("seen", "see"),
+    ("sought", "seek"),
+    ("sold", "sell"),
+    ("sent", "send"),
+    ("shook", "shake"),
+    ("shaken", "shake"),
+    ("shone", "shine"),
+    ("shot", "shoot"),
+    ("shown", "show"),
+    ("shrank", "shrink"),
+    ("shrunk", "shrink"),
+    ("sang", "sing"),
+    ("sung", "sing"),
+    ("sank", "sink"),
+    ("sunk", "sink"),
+    ("sat", "sit"),
+    ("slept", "sleep"),
+    ("slid", "slide"),
+    ("spoke", "speak"),
+    ("spoken", "speak"),
+    ("sped", "speed"),
+    ("spent", "spend"),
+    ("spun", "spin"),
+    ("sprang", "spring"),
+    ("sprung", "spring"),
+    ("stood", "stand"),
+    ("stole", "steal"),
+    ("stolen", "steal"),
+    ("stuck", "stick"),
+    ("stung", "sting"),
+    ("struck", "strike"),
+    ("strove", "strive"),
+    ("striven", "strive"),
+    ("swore", "swear"),
+    ("sworn", "swear"),
+    ("swept", "sweep"),
+    ("swam", "swim"),
+    ("swum", "swim"),
+    ("swung", "swing"),
+    ("took", "take"),
+    ("taken", "take"),
+    ("taught", "teach"),
+    ("tore", "tear"),
+    ("torn", "tear"),
+    ("told", "tell"),
+    ("thought", "think"),
+    ("threw", "throw"),
+    ("thrown", "throw"),
+    ("understood", "understand"),
+    ("woke", "wake"),
+    ("woken", "wake"),
+    ("wore", "wear"),
+    ("worn", "wear"),
+    ("wove", "weave"),
+    ("woven", "weave"),
+    ("wept", "weep"),
+    ("won", "win"),
+    ("wound", "wind"),
+    ("wrote", "write"),
+    ("written", "write"),
+    // Nouns
+    ("men", "man"),
+    ("women", "woman"),
+    ("children", "child"),
+    ("people", "person"),
+    ("feet", "foot"),
+    ("teeth", "tooth"),
+    ("geese", "goose"),
+    ("mice", "mouse"),
+    ("lice", "louse"),
+    ("oxen", "ox"),
+    ("dice", "die"),
+    ("criteria", "criterion"),
+    ("phenomena", "phenomenon"),
+    ("data", "datum"),
+    ("media", "medium"),
+    ("bacteria", "bacterium"),
+    ("curricula", "curriculum"),
+    ("alumni", "alumnus"),
+    ("cacti", "cactus"),
+    ("fungi", "fungus"),
+    ("nuclei", "nucleus"),
+    ("stimuli", "stimulus"),
+    ("syllabi", "syllabus"),
+    ("appendices", "appendix"),
+    ("indices", "index"),
+    ("matrices", "matrix"),
+    ("vertices", "vertex"),
+    // Adjectives and adverbs
+    ("better", "good"),
+    ("best", "good"),
+    ("worse", "bad"),
+    ("worst", "bad"),
+    ("more", "much"),
+    ("most", "much"),
+    ("less", "little"),
+    ("least", "little"),
+    ("further", "far"),
+    ("furthest", "far"),
+    ("farther", "far"),
+    ("farthest", "far"),
+    ("elder", "old"),
+    ("eldest", "old"),
+];
+
+/// Suffix rules as (suffix, replacement), most specific first
+const SUFFIXES: &[(&str, &str)] = &[
+    // analyses → analysis, crises → crisis
+    ("yses", "ysis"),
+    ("ises", "isis"),
+    // wolves → wolf, knives → knife
+    ("ves", "f"),
+    ("ves", "fe"),
+    // studies → study, studied → study, happier → happy
+    ("ies", "y"),
+    ("ied", "y"),
+    ("iest", "y"),
+    ("ier", "y"),
+    // boxes → box, watches → watch
+    ("es", ""),
+    ("s", ""),
+    // making → make, making → mak
+    ("ing", "e"),
+    ("ing", ""),
+    // liked → like, played → play
+    ("ed", "e"),
+    ("ed", ""),
+    // larger → large, taller → tall
+    ("est", "e"),
+    ("est", ""),
+    ("er", "e"),
+    ("er", ""),
+    // quickly → quick
+    ("ly", ""),
+];
+
+/// Candidate headwords for an inflected English word, most likely first
+///
+/// Candidates are guesses; callers keep the first one that is actually in
+/// the dictionary. The word itself is never included.
+pub fn lemma_candidates(word: &str) -> Vec<String> {
+    let word = word.to_lowercase();
+    if word.len() < 3 || !word.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<String> = Vec::new();
+    let mut push = |candidate: String| {
+        if candidate != word && !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    };
+
+    for (form, lemma) in IRREGULAR {
+        if *form == word {
+            push(lemma.to_string());
+        }
+    }
+
+    for (suffix, replacement) in SUFFIXES {
+        let Some(stem) = word.strip_suffix(suffix) else {
+            continue;
+        };
+        if stem.len() < 2 {
+            continue;
+        }
+        // running → run, stopped → stop, bigger → big
+        if replacement.is_empty() && matches!(*suffix, "ing" | "ed" | "er" | "est") {
+            if let Some(undoubled) = undouble(stem) {
+                push(undoubled.to_string());
+            }
+        }
+        push(format!("{}{}", stem, replacement));
+    }
+
+    candidates
+}
+
+/// Drop a doubled final consonant: "runn" → "run"
+fn undouble(stem: &str) -> Option<&str> {
+    let bytes = stem.as_bytes();
+    let n = bytes.len();
+    if n >= 3 && bytes[n - 1] == bytes[n - 2] && !b"aeioulsz".contains(&bytes[n - 1]) {
+        Some(&stem[..n - 1])
+    } else {
+        None
+    }
+}
@@ -0,0 +1,108 @@
+// Query normalization: map equivalent spellings of a query to one cache key
+use crate::domain::chinese::to_simplified;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// Which normalization steps to apply, in the order they run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NormalizeOptions {
+    /// Full-width ASCII and the ideographic space to their half-width forms
+    #[serde(default = "default_true")]
+    pub half_width: bool,
+    /// Unicode NFKC (compatibility composition)
+    #[serde(default = "default_true")]
+    pub nfkc: bool,
+    /// Trim and collapse runs of whitespace to a single space
+    #[serde(default = "default_true")]
+    pub collapse_whitespace: bool,
+    /// Lowercase English so "Apple" and "APPLE" share a key (not applied to long text
+    /// or to queries from other languages)
+    #[serde(default = "default_true")]
+    pub fold_case: bool,
+    /// Traditional Chinese to simplified, so 電腦 finds 电脑 (not applied to long text)
+    #[serde(default = "default_true")]
+    pub simplified: bool,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            half_width: true,
+            nfkc: true,
+            collapse_whitespace: true,
+            fold_case: true,
+            simplified: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl NormalizeOptions {
+    /// Normalize a query into its cache key
+    pub fn normalize(&self, query: &str) -> String {
+        let mut text = if self.half_width {
+            query.chars().map(to_half_width).collect()
+        } else {
+            query.to_string()
+        };
+        if self.nfkc {
+            text = text.nfkc().collect();
+        }
+        if self.collapse_whitespace {
+            text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        if self.fold_case {
+            text = text.to_lowercase();
+        }
+        if self.simplified {
+            text = to_simplified(&text);
+        }
+        text
+    }
+
+    /// Options for long text (`-t`): translate the text as written, only tidy it up
+    pub fn for_long_text(&self) -> Self {
+        Self {
+            fold_case: false,
+            simplified: false,
+            ..self.clone()
+        }
+    }
+
+    /// Options for a query translated from `lang`: only English (or undetected) text folds case
+    pub fn for_source(&self, lang: &str) -> Self {
+        Self {
+            fold_case: self.fold_case && matches!(lang, "en" | "auto"),
+            ..self.clone()
+        }
+    }
+
+    /// Stable summary of the enabled steps, stored to detect when keys need rewriting
+    pub fn fingerprint(&self) -> String {
+        let steps = [
+            (self.half_width, "width"),
+            (self.nfkc, "nfkc"),
+            (self.collapse_whitespace, "whitespace"),
+            (self.fold_case, "case"),
+            (self.simplified, "simplified"),
+        ];
+        let enabled: Vec<&str> = steps
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, name)| *name)
+            .collect();
+        format!("v1:{}", enabled.join(","))
+    }
+}
+
+/// Full-width forms (U+FF01..U+FF5E) and U+3000 to their ASCII equivalents
+fn to_half_width(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
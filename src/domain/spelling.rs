@@ -0,0 +1,118 @@
+// Spelling suggestions: a BK-tree over headwords keyed by edit distance
+use std::collections::HashMap;
+
+struct Node {
+    word: String,
+    key: Vec<char>,
+    children: HashMap<usize, usize>,
+}
+
+/// BK-tree answering "which words are within distance d of this one"
+///
+/// Each child edge is labelled with its distance to the parent, so a search
+/// only descends into edges within `tolerance` of the query's distance to the node.
+#[derive(Default)]
+pub struct BkTree {
+    nodes: Vec<Node>,
+}
+
+impl BkTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, word: String) {
+        let key = fold(&word);
+        if self.nodes.is_empty() {
+            self.nodes.push(Node {
+                word,
+                key,
+                children: HashMap::new(),
+            });
+            return;
+        }
+
+        let mut current = 0;
+        loop {
+            let distance = edit_distance(&self.nodes[current].key, &key);
+            if distance == 0 {
+                return;
+            }
+            match self.nodes[current].children.get(&distance) {
+                Some(&child) => current = child,
+                None => {
+                    let index = self.nodes.len();
+                    self.nodes.push(Node {
+                        word,
+                        key,
+                        children: HashMap::new(),
+                    });
+                    self.nodes[current].children.insert(distance, index);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Words within `tolerance` edits of `word`, closest first, at most `limit`
+    pub fn find(&self, word: &str, tolerance: usize, limit: usize) -> Vec<String> {
+        if self.nodes.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let key = fold(word);
+        let mut matches = Vec::new();
+        let mut pending = vec![0];
+        while let Some(index) = pending.pop() {
+            let node = &self.nodes[index];
+            let distance = edit_distance(&node.key, &key);
+            if distance <= tolerance {
+                matches.push((distance, node));
+            }
+            for (&edge, &child) in &node.children {
+                if edge + tolerance >= distance && edge <= distance + tolerance {
+                    pending.push(child);
+                }
+            }
+        }
+
+        // Equally close words: prefer similar length, then alphabetical
+        matches.sort_by_key(|(distance, node)| {
+            (*distance, node.key.len().abs_diff(key.len()), &node.word)
+        });
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, node)| node.word.clone())
+            .collect()
+    }
+}
+
+/// Edit distance allowed for a word: short words tolerate a single typo
+pub fn tolerance_for(word: &str) -> usize {
+    if word.chars().count() <= 4 {
+        1
+    } else {
+        2
+    }
+}
+
+/// Case-folded chars, the form words are compared in
+fn fold(word: &str) -> Vec<char> {
+    word.chars().flat_map(char::to_lowercase).collect()
+}
+
+/// Levenshtein distance over chars
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
@@ -1,17 +1,19 @@
 use crate::domain::error::KdError;
-use crate::domain::model::QueryResult;
+use crate::domain::model::{MemoryCacheStats, OnlineSource, QueryResult, TranslateRequest};
 use async_trait::async_trait;
 
 /// Trait for translation services
 ///
 /// This trait provides an abstraction for different translation providers.
-/// Implementations can be swapped without changing the calling code.
-/// Reserved for future multi-provider support and testing.
+/// Implementations are assembled into an ordered chain by the provider
+/// registry, so they can be swapped without changing the calling code.
 #[async_trait]
-#[allow(dead_code)]
-pub trait Translator {
-    /// Translate a query string
-    async fn translate(&self, query: &str) -> Result<QueryResult, KdError>;
+pub trait Translator: Send + Sync {
+    /// The online source recorded on results produced by this provider
+    fn source(&self) -> OnlineSource;
+
+    /// Translate a query between the languages named in the request
+    async fn translate(&self, request: &TranslateRequest) -> Result<QueryResult, KdError>;
 }
 
 /// Trait for database operations
@@ -23,25 +25,41 @@ pub trait Translator {
 #[allow(dead_code)]
 pub trait Database {
     /// Query cache by query string
-    async fn query_cache(&self, query: &str) -> Result<Option<QueryResult>, KdError>;
+    async fn query_cache(&self, ns: &str, query: &str) -> Result<Option<QueryResult>, KdError>;
 
     /// Insert a query result into cache
-    async fn insert_cache(&self, query: &str, result: &QueryResult) -> Result<(), KdError>;
+    async fn insert_cache(
+        &self,
+        ns: &str,
+        query: &str,
+        result: &QueryResult,
+    ) -> Result<(), KdError>;
 
     /// Batch insert multiple query results (for migration)
-    async fn batch_insert_cache(&self, items: Vec<(String, QueryResult)>)
-        -> Result<usize, KdError>;
+    async fn batch_insert_cache(
+        &self,
+        ns: &str,
+        items: Vec<(String, QueryResult)>,
+    ) -> Result<usize, KdError>;
 }
 
 /// Trait for cache operations
 ///
-/// This trait abstracts in-memory cache operations.
-/// Reserved for future cache abstraction and testing.
-#[allow(dead_code)]
-pub trait Cache {
-    /// Get a cached result by key
+/// This trait abstracts the in-memory cache in front of the database, so its
+/// eviction policy can change without touching the query path.
+pub trait Cache: Send + Sync {
+    /// Get a cached result by key, counting a hit or a miss
     fn get(&self, key: &str) -> Option<QueryResult>;
 
-    /// Insert a result into cache
+    /// Insert a result into cache, evicting others if it's over its limits
     fn insert(&self, key: String, value: QueryResult);
+
+    /// Drop every entry (the counters are kept)
+    fn clear(&self);
+
+    /// Number of entries currently held
+    fn len(&self) -> usize;
+
+    /// Size and hit/miss/eviction counters
+    fn stats(&self) -> MemoryCacheStats;
 }
@@ -1,7 +1,10 @@
 use crate::domain::error::KdError;
+use crate::domain::normalize::NormalizeOptions;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
@@ -20,10 +23,255 @@ pub struct Config {
     pub enable_emoji: bool,
     #[serde(default)]
     pub freq_alert: bool,
+    /// How many "did you mean" suggestions to show for a missed word (0 disables)
+    #[serde(default = "default_max_suggestions")]
+    pub max_suggestions: usize,
+    /// Default source language for `--from` ("auto" detects it from the query)
+    #[serde(default = "default_language")]
+    pub source_lang: String,
+    /// Default target language for `--to` ("auto": Chinese → English, others → Chinese)
+    #[serde(default = "default_language")]
+    pub target_lang: String,
     #[serde(default)]
     pub logging: Logging,
+    /// How queries are normalized into cache keys
+    #[serde(default)]
+    pub normalization: NormalizeOptions,
+    /// Online providers, tried in this order until one answers
+    #[serde(default = "default_providers")]
+    pub providers: Vec<String>,
+    /// Online providers used for long text (`-t`), tried in this order
+    #[serde(default = "default_long_text_providers")]
+    pub long_text_providers: Vec<String>,
     #[serde(default)]
     pub youdao: YoudaoConfig,
+    #[serde(default)]
+    pub bing: BingConfig,
+    #[serde(default)]
+    pub google: GoogleConfig,
+    #[serde(default)]
+    pub libretranslate: LibreTranslateConfig,
+    /// Generic HTTP JSON backends, referenced by name in the provider lists
+    #[serde(default)]
+    pub http_templates: Vec<HttpTemplateConfig>,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub update: UpdateConfig,
+}
+
+/// Where `--update-dict` fetches the offline dictionary from
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateConfig {
+    /// Sources of the dictionary archive, tried one after another until one succeeds
+    #[serde(default = "default_mirrors")]
+    pub mirrors: Vec<MirrorConfig>,
+    /// Archive URL from before mirror lists; when set it's the only mirror
+    pub url: Option<String>,
+    /// How the mirror to try first is picked: "latency", "order" or "geolocation"
+    #[serde(default = "default_mirror_selection")]
+    pub selection: String,
+    /// Hex-encoded ed25519 key; when set, the archive manifest must carry a valid signature
+    pub public_key: Option<String>,
+    /// How many times an interrupted download is resumed before giving up
+    #[serde(default = "default_download_retries")]
+    pub retries: u32,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            mirrors: default_mirrors(),
+            url: None,
+            selection: default_mirror_selection(),
+            public_key: None,
+            retries: default_download_retries(),
+        }
+    }
+}
+
+impl UpdateConfig {
+    /// Mirrors to download from: the legacy `url` alone when set, else `mirrors`
+    pub fn mirrors(&self) -> Vec<MirrorConfig> {
+        match &self.url {
+            Some(url) => vec![MirrorConfig {
+                url: url.clone(),
+                name: None,
+                region: None,
+            }],
+            None => self.mirrors.clone(),
+        }
+    }
+}
+
+/// A source of the dictionary archive (`http(s)://` or `file://`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MirrorConfig {
+    pub url: String,
+    /// Shown while downloading; the URL when unset
+    pub name: Option<String>,
+    /// Country code preferred by `selection = "geolocation"`, e.g. "CN"
+    pub region: Option<String>,
+}
+
+impl MirrorConfig {
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.url)
+    }
+}
+
+/// Freshness of cached online results
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CacheConfig {
+    /// How long an online result is served before it's refreshed ("30d", "12h";
+    /// "never" keeps it forever)
+    #[serde(default = "default_ttl")]
+    pub ttl: String,
+    /// Per-source overrides of `ttl`, keyed by provider name
+    #[serde(default)]
+    pub source_ttl: BTreeMap<String, String>,
+    /// Most results held in memory by long-running modes (0 for no limit)
+    #[serde(default = "default_memory_entries")]
+    pub memory_entries: usize,
+    /// Most memory those results may take ("64MB", "512KB"; "0" for no limit)
+    #[serde(default = "default_memory_size")]
+    pub memory_size: String,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: default_ttl(),
+            source_ttl: BTreeMap::new(),
+            memory_entries: default_memory_entries(),
+            memory_size: default_memory_size(),
+        }
+    }
+}
+
+/// `ttl` and `source_ttl` parsed, `None` standing for "never"
+#[derive(Debug, Clone, Default)]
+pub struct CacheTtl {
+    default: Option<Duration>,
+    sources: BTreeMap<String, Option<Duration>>,
+}
+
+impl CacheTtl {
+    /// TTL of results from `source`, `None` when they never expire
+    pub fn for_source(&self, source: &str) -> Option<Duration> {
+        self.sources.get(source).copied().unwrap_or(self.default)
+    }
+}
+
+impl CacheConfig {
+    /// Parse `ttl` and `source_ttl`
+    pub fn ttl(&self) -> Result<CacheTtl, KdError> {
+        let parse = |ttl: &str, what: &str| {
+            if ttl.trim() == "never" {
+                return Ok(None);
+            }
+            parse_duration(ttl)
+                .map(Some)
+                .map_err(|e| KdError::Config(format!("cache TTL for {}: {}", what, e)))
+        };
+        Ok(CacheTtl {
+            default: parse(&self.ttl, "all sources")?,
+            sources: self
+                .source_ttl
+                .iter()
+                .map(|(source, ttl)| Ok((source.clone(), parse(ttl, source)?)))
+                .collect::<Result<_, KdError>>()?,
+        })
+    }
+
+    /// `memory_size` in bytes
+    pub fn memory_bytes(&self) -> Result<usize, KdError> {
+        parse_size(&self.memory_size)
+            .map_err(|e| KdError::Config(format!("cache memory_size: {}", e)))
+    }
+}
+
+/// Parse a duration such as "30d", "12h", "90m", "1w" or "1d12h"
+pub fn parse_duration(text: &str) -> Result<Duration, KdError> {
+    let invalid = || KdError::Config(format!("invalid duration {:?}", text));
+    let text = text.trim();
+    if text == "0" {
+        return Ok(Duration::ZERO);
+    }
+
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = number.parse().map_err(|_| invalid())?;
+        seconds = value
+            .checked_mul(unit)
+            .and_then(|s| seconds.checked_add(s))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() || text.is_empty() {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Parse a size such as "64MB", "512KB", "1GB" or a plain byte count
+pub fn parse_size(text: &str) -> Result<usize, KdError> {
+    let invalid = || KdError::Config(format!("invalid size {:?}", text));
+    let upper = text.trim().to_uppercase();
+    let split = upper
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(upper.len());
+    let (number, unit) = upper.split_at(split);
+    let unit: usize = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return Err(invalid()),
+    };
+    let number: usize = number.parse().map_err(|_| invalid())?;
+    number.checked_mul(unit).ok_or_else(invalid)
+}
+
+/// Settings for `kd serve`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerConfig {
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    /// Origins allowed to call the server from a browser ("*" allows any)
+    #[serde(default)]
+    pub cors_origins: Vec<String>,
+    /// When set, requests must carry `Authorization: Bearer <token>`
+    pub token: Option<String>,
+    /// Lookups kept for `/history`, older ones are pruned (0 disables history)
+    #[serde(default = "default_history_limit")]
+    pub history_limit: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen: default_listen(),
+            cors_origins: Vec::new(),
+            token: None,
+            history_limit: default_history_limit(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -35,12 +283,115 @@ pub struct Logging {
     pub level: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct YoudaoConfig {
+    #[serde(default = "default_enable")]
+    pub enable: bool,
     pub api_id: Option<String>,
     pub api_key: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BingConfig {
+    #[serde(default = "default_enable")]
+    pub enable: bool,
+    /// Base URL of the Bing dictionary API
+    #[serde(default = "default_bing_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "default_bing_app_id")]
+    pub app_id: String,
+}
+
+impl Default for BingConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            endpoint: default_bing_endpoint(),
+            app_id: default_bing_app_id(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GoogleConfig {
+    #[serde(default = "default_enable")]
+    pub enable: bool,
+    /// Base URL of the Google Cloud Translation API
+    #[serde(default = "default_google_endpoint")]
+    pub endpoint: String,
+    pub api_key: Option<String>,
+}
+
+impl Default for GoogleConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            endpoint: default_google_endpoint(),
+            api_key: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LibreTranslateConfig {
+    #[serde(default = "default_enable")]
+    pub enable: bool,
+    /// Base URL of the self-hosted LibreTranslate instance
+    #[serde(default)]
+    pub endpoint: String,
+    pub api_key: Option<String>,
+}
+
+impl Default for LibreTranslateConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            endpoint: String::new(),
+            api_key: None,
+        }
+    }
+}
+
+/// A translation backend described by a request template and field mapping
+///
+/// `url` and `body` may contain `{{query}}`, `{{from}}` and `{{to}}`
+/// placeholders, which are URL-encoded and JSON-escaped respectively.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HttpTemplateConfig {
+    pub name: String,
+    #[serde(default = "default_enable")]
+    pub enable: bool,
+    pub url: String,
+    #[serde(default = "default_template_method")]
+    pub method: String,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub fields: FieldMapping,
+}
+
+/// JSONPath-like expressions mapping a response into `QueryResult` fields
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FieldMapping {
+    pub translations: Option<String>,
+    pub pronunciation: Option<String>,
+    pub pronunciation_us: Option<String>,
+    pub pronunciation_uk: Option<String>,
+    pub examples_original: Option<String>,
+    pub examples_translation: Option<String>,
+}
+
+impl Default for YoudaoConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            api_id: None,
+            api_key: None,
+        }
+    }
+}
+
 impl Default for Logging {
     fn default() -> Self {
         Self {
@@ -62,8 +413,21 @@ impl Default for Config {
             clear_screen: false,
             enable_emoji: true,
             freq_alert: false,
+            max_suggestions: default_max_suggestions(),
+            source_lang: default_language(),
+            target_lang: default_language(),
             logging: Logging::default(),
+            normalization: NormalizeOptions::default(),
+            providers: default_providers(),
+            long_text_providers: default_long_text_providers(),
             youdao: YoudaoConfig::default(),
+            bing: BingConfig::default(),
+            google: GoogleConfig::default(),
+            libretranslate: LibreTranslateConfig::default(),
+            http_templates: Vec::new(),
+            server: ServerConfig::default(),
+            cache: CacheConfig::default(),
+            update: UpdateConfig::default(),
         }
     }
 }
@@ -92,6 +456,66 @@ fn default_enable() -> bool {
 fn default_log_level() -> String {
     "WARN".to_string()
 }
+fn default_max_suggestions() -> usize {
+    5
+}
+fn default_language() -> String {
+    "auto".to_string()
+}
+fn default_providers() -> Vec<String> {
+    vec!["youdao".to_string()]
+}
+fn default_long_text_providers() -> Vec<String> {
+    vec!["google".to_string(), "youdao".to_string()]
+}
+fn default_bing_endpoint() -> String {
+    "https://cn.bing.com".to_string()
+}
+fn default_bing_app_id() -> String {
+    "371E7B2AF0F9B84EC491D731DF90A55719C7D209".to_string()
+}
+fn default_google_endpoint() -> String {
+    "https://translation.googleapis.com".to_string()
+}
+fn default_listen() -> String {
+    "127.0.0.1:8765".to_string()
+}
+fn default_history_limit() -> usize {
+    1000
+}
+fn default_ttl() -> String {
+    "30d".to_string()
+}
+fn default_memory_entries() -> usize {
+    10_000
+}
+fn default_memory_size() -> String {
+    "64MB".to_string()
+}
+fn default_mirrors() -> Vec<MirrorConfig> {
+    vec![
+        MirrorConfig {
+            url: "https://gitee.com/void_kmz/kd/releases/download/v0.0.1/kd_data.zip".to_string(),
+            name: Some("Gitee (CN)".to_string()),
+            region: Some("CN".to_string()),
+        },
+        MirrorConfig {
+            url: "https://raw.githubusercontent.com/Karmenzind/static/main/kd/kd_data.zip"
+                .to_string(),
+            name: Some("GitHub (Global)".to_string()),
+            region: None,
+        },
+    ]
+}
+fn default_mirror_selection() -> String {
+    "latency".to_string()
+}
+fn default_download_retries() -> u32 {
+    5
+}
+fn default_template_method() -> String {
+    "POST".to_string()
+}
 
 pub fn get_config_path() -> Option<PathBuf> {
     dirs::config_dir().map(|p| p.join("kd").join("config.toml"))
@@ -106,6 +530,16 @@ pub fn get_database_path(_config: &Config) -> PathBuf {
         .join("kd.db")
 }
 
+/// Get daemon socket path (next to the database)
+pub fn get_socket_path(config: &Config) -> PathBuf {
+    get_database_path(config).with_file_name("kd.sock")
+}
+
+/// Get interactive mode history file path (next to the database)
+pub fn get_repl_history_path(config: &Config) -> PathBuf {
+    get_database_path(config).with_file_name("repl_history")
+}
+
 pub fn load_config() -> Result<Config, KdError> {
     let config_path = get_config_path();
 
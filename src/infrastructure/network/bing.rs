@@ -0,0 +1,194 @@
+use crate::domain::error::KdError;
+use crate::domain::model::{OnlineSource, QueryResult, QuerySource, TranslateRequest};
+use crate::domain::traits::Translator;
+use crate::infrastructure::config::BingConfig;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+
+// Bing dictionary API response structures
+#[derive(Deserialize, Debug)]
+struct BingResponse {
+    #[serde(default)]
+    value: Vec<BingEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct BingEntry {
+    #[serde(default)]
+    meaning_groups: Vec<MeaningGroup>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MeaningGroup {
+    #[serde(default)]
+    parts_of_speech: Vec<PartOfSpeech>,
+    #[serde(default)]
+    meanings: Vec<Meaning>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PartOfSpeech {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Meaning {
+    #[serde(default)]
+    rich_definitions: Vec<RichDefinition>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RichDefinition {
+    #[serde(default)]
+    fragments: Vec<Fragment>,
+    #[serde(default)]
+    examples: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Fragment {
+    text: String,
+}
+
+impl MeaningGroup {
+    fn fragment_text(&self) -> String {
+        self.meanings
+            .iter()
+            .flat_map(|m| &m.rich_definitions)
+            .flat_map(|d| &d.fragments)
+            .map(|f| f.text.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("；")
+    }
+}
+
+/// Bing dictionary translator implementation
+pub struct BingTranslator {
+    client: Client,
+    config: BingConfig,
+}
+
+impl BingTranslator {
+    pub fn new(client: Client, config: BingConfig) -> Self {
+        Self { client, config }
+    }
+}
+
+#[async_trait]
+impl Translator for BingTranslator {
+    fn source(&self) -> OnlineSource {
+        OnlineSource::Bing
+    }
+
+    async fn translate(&self, request: &TranslateRequest) -> Result<QueryResult, KdError> {
+        // The dictionary only covers English ↔ Chinese, so `from`/`to` aren't sent
+        let supported = matches!(request.from.as_str(), "en" | "zh" | "auto")
+            && matches!(request.to.as_str(), "en" | "zh");
+        if !supported {
+            return Err(KdError::Config(format!(
+                "Bing dictionary doesn't translate {} → {}",
+                request.from, request.to
+            )));
+        }
+        query_bing_impl(&self.client, &self.config, &request.query).await
+    }
+}
+
+async fn query_bing_impl(
+    client: &Client,
+    config: &BingConfig,
+    query: &str,
+) -> Result<QueryResult, KdError> {
+    let url = format!(
+        "{}/api/v7/dictionarywords/search",
+        config.endpoint.trim_end_matches('/')
+    );
+    let params = [
+        ("q", query),
+        ("appid", config.app_id.as_str()),
+        ("mkt", "zh-cn"),
+        ("pname", "bingdict"),
+    ];
+
+    let response = client.get(&url).query(&params).send().await?;
+    if !response.status().is_success() {
+        return Err(KdError::Api(format!(
+            "Bing API Error: HTTP {}",
+            response.status()
+        )));
+    }
+    let response = response.json::<BingResponse>().await?;
+
+    let mut result = QueryResult::new(query.to_string(), false);
+    result.source = QuerySource::Online(OnlineSource::Bing);
+
+    let groups = response
+        .value
+        .into_iter()
+        .next()
+        .map(|entry| entry.meaning_groups)
+        .unwrap_or_default();
+
+    for group in &groups {
+        let Some(pos) = group.parts_of_speech.first() else {
+            continue;
+        };
+
+        match pos.description.as_str() {
+            // Pronunciation groups are labelled 美 (US) / 英 (UK)
+            "发音" => {
+                let text = group.fragment_text();
+                if text.is_empty() {
+                    continue;
+                }
+                match pos.name.as_str() {
+                    "美" => result.pronunciation_us = Some(text),
+                    "英" => result.pronunciation_uk = Some(text),
+                    _ => {}
+                }
+            }
+            // Examples come as flat [original, translation, ...] pairs
+            "例句" => {
+                for definition in group.meanings.iter().flat_map(|m| &m.rich_definitions) {
+                    for pair in definition.examples.chunks(2) {
+                        if let [orig, trans] = pair {
+                            result.examples.push((orig.clone(), trans.clone()));
+                        }
+                    }
+                }
+            }
+            // Part-of-speech translations, e.g. "int." → "你好；喂"
+            "快速释义" => {
+                let text = group.fragment_text();
+                if text.is_empty() {
+                    continue;
+                }
+                if pos.name.is_empty() {
+                    result.translations.push(text);
+                } else {
+                    result.translations.push(format!("{} {}", pos.name, text));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    result.pronunciation = result
+        .pronunciation_us
+        .clone()
+        .or_else(|| result.pronunciation_uk.clone());
+
+    if !result.translations.is_empty() || result.pronunciation.is_some() {
+        result.found = true;
+    }
+
+    Ok(result)
+}
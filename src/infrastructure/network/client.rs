@@ -1,5 +1,5 @@
 use crate::domain::error::KdError;
-use crate::domain::model::{OnlineSource, QueryResult};
+use crate::domain::model::{OnlineSource, QueryResult, TranslateRequest};
 use crate::domain::traits::Translator;
 use crate::infrastructure::config::Config;
 use async_trait::async_trait;
@@ -36,16 +36,12 @@ struct WebTranslation {
 }
 
 /// Youdao translator implementation
-///
-/// Reserved for future use with Translator trait abstraction.
-#[allow(dead_code)]
 pub struct YoudaoTranslator {
     client: Client,
     config: Config,
 }
 
 impl YoudaoTranslator {
-    #[allow(dead_code)]
     pub fn new(client: Client, config: Config) -> Self {
         Self { client, config }
     }
@@ -53,26 +49,31 @@ impl YoudaoTranslator {
 
 #[async_trait]
 impl Translator for YoudaoTranslator {
-    async fn translate(&self, query: &str) -> Result<QueryResult, KdError> {
-        query_youdao_impl(&self.client, &self.config, query).await
+    fn source(&self) -> OnlineSource {
+        OnlineSource::Youdao
+    }
+
+    async fn translate(&self, request: &TranslateRequest) -> Result<QueryResult, KdError> {
+        query_youdao_impl(&self.client, &self.config, request).await
     }
 }
 
-// Public function for backward compatibility
-pub async fn query_youdao(
-    client: &Client,
-    config: &Config,
-    query: &str,
-) -> Result<QueryResult, KdError> {
-    query_youdao_impl(client, config, query).await
+/// Youdao's code for a language; it spells the Chinese scripts "zh-CHS"/"zh-CHT"
+fn youdao_language(code: &str) -> &str {
+    match code {
+        "zh" => "zh-CHS",
+        "zh-TW" => "zh-CHT",
+        other => other,
+    }
 }
 
 // Internal implementation
 async fn query_youdao_impl(
     client: &Client,
     config: &Config,
-    query: &str,
+    request: &TranslateRequest,
 ) -> Result<QueryResult, KdError> {
+    let query = request.query.as_str();
     let api_id = config.youdao.api_id.as_deref().unwrap_or("");
     let api_key = config.youdao.api_key.as_deref().unwrap_or("");
 
@@ -110,8 +111,8 @@ async fn query_youdao_impl(
 
     let params = [
         ("q", query),
-        ("from", "auto"),
-        ("to", "auto"),
+        ("from", youdao_language(&request.from)),
+        ("to", youdao_language(&request.to)),
         ("appKey", api_id),
         ("salt", &salt),
         ("sign", &sign),
@@ -156,7 +157,7 @@ async fn query_youdao_impl(
         )));
     }
 
-    let mut result = QueryResult::new(query.to_string(), false);
+    let mut result = QueryResult::new(query.to_string(), request.is_long_text);
     result.source = crate::domain::model::QuerySource::Online(OnlineSource::Youdao);
 
     if let Some(trans) = response.translations {
@@ -0,0 +1,136 @@
+use crate::domain::error::KdError;
+use crate::domain::model::{OnlineSource, QueryResult, QuerySource, TranslateRequest};
+use crate::domain::traits::Translator;
+use crate::infrastructure::config::GoogleConfig;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+// Google Cloud Translation (v2) request/response structures
+#[derive(Serialize, Debug)]
+struct GoogleRequest<'a> {
+    q: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'a str>,
+    target: &'a str,
+    format: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct GoogleResponse {
+    data: Option<GoogleData>,
+    error: Option<GoogleError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GoogleData {
+    #[serde(default)]
+    translations: Vec<GoogleTranslation>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GoogleTranslation {
+    translated_text: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GoogleError {
+    code: i64,
+    message: String,
+}
+
+/// Google translator implementation, mainly used for long text (`-t`)
+pub struct GoogleTranslator {
+    client: Client,
+    config: GoogleConfig,
+}
+
+impl GoogleTranslator {
+    pub fn new(client: Client, config: GoogleConfig) -> Self {
+        Self { client, config }
+    }
+}
+
+#[async_trait]
+impl Translator for GoogleTranslator {
+    fn source(&self) -> OnlineSource {
+        OnlineSource::Google
+    }
+
+    async fn translate(&self, request: &TranslateRequest) -> Result<QueryResult, KdError> {
+        query_google_impl(&self.client, &self.config, request).await
+    }
+}
+
+/// Google's code for an ISO 639-1 language; Chinese needs its script variant
+fn google_language(code: &str) -> &str {
+    match code {
+        "zh" => "zh-CN",
+        other => other,
+    }
+}
+
+async fn query_google_impl(
+    client: &Client,
+    config: &GoogleConfig,
+    request: &TranslateRequest,
+) -> Result<QueryResult, KdError> {
+    let query = request.query.as_str();
+    let api_key = config.api_key.as_deref().unwrap_or("");
+    if api_key.is_empty() {
+        return Err(KdError::Config("Google API Key not configured".to_string()));
+    }
+
+    let url = format!(
+        "{}/language/translate/v2",
+        config.endpoint.trim_end_matches('/')
+    );
+    let body = GoogleRequest {
+        q: query,
+        // Leaving out the source lets Google detect it
+        source: Some(request.from.as_str())
+            .filter(|from| *from != "auto")
+            .map(google_language),
+        target: google_language(&request.to),
+        format: "text",
+    };
+
+    let response = client
+        .post(&url)
+        .query(&[("key", api_key)])
+        .json(&body)
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.bytes().await?;
+
+    // Error bodies are usually JSON with a message, but proxies may send anything
+    let parsed = serde_json::from_slice::<GoogleResponse>(&body);
+    if let Some(error) = parsed.as_ref().ok().and_then(|r| r.error.as_ref()) {
+        return Err(KdError::Api(format!(
+            "Google API Error {}: {}",
+            error.code, error.message
+        )));
+    }
+    if !status.is_success() {
+        return Err(KdError::Api(format!("Google API Error: HTTP {}", status)));
+    }
+    let response = parsed?;
+
+    let mut result = QueryResult::new(query.to_string(), true);
+    result.source = QuerySource::Online(OnlineSource::Google);
+
+    if let Some(data) = response.data {
+        result.translations = data
+            .translations
+            .into_iter()
+            .map(|t| t.translated_text)
+            .filter(|t| !t.is_empty())
+            .collect();
+    }
+
+    result.found = !result.translations.is_empty();
+
+    Ok(result)
+}
@@ -0,0 +1,105 @@
+use crate::domain::error::KdError;
+use crate::domain::model::{OnlineSource, QueryResult, QuerySource, TranslateRequest};
+use crate::domain::traits::Translator;
+use crate::infrastructure::config::LibreTranslateConfig;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+// LibreTranslate API request/response structures
+#[derive(Serialize, Debug)]
+struct LibreRequest<'a> {
+    q: &'a str,
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LibreResponse {
+    translated_text: Option<String>,
+    error: Option<String>,
+}
+
+/// Translator for a self-hosted LibreTranslate-compatible endpoint
+pub struct LibreTranslator {
+    client: Client,
+    config: LibreTranslateConfig,
+}
+
+impl LibreTranslator {
+    pub fn new(client: Client, config: LibreTranslateConfig) -> Self {
+        Self { client, config }
+    }
+}
+
+#[async_trait]
+impl Translator for LibreTranslator {
+    fn source(&self) -> OnlineSource {
+        OnlineSource::LibreTranslate
+    }
+
+    async fn translate(&self, request: &TranslateRequest) -> Result<QueryResult, KdError> {
+        query_libre_impl(&self.client, &self.config, request).await
+    }
+}
+
+/// LibreTranslate's code for a language; traditional Chinese is "zt"
+fn libre_language(code: &str) -> &str {
+    match code {
+        "zh-TW" => "zt",
+        other => other,
+    }
+}
+
+async fn query_libre_impl(
+    client: &Client,
+    config: &LibreTranslateConfig,
+    request: &TranslateRequest,
+) -> Result<QueryResult, KdError> {
+    let query = request.query.as_str();
+    if config.endpoint.is_empty() {
+        return Err(KdError::Config(
+            "LibreTranslate endpoint not configured".to_string(),
+        ));
+    }
+
+    let url = format!("{}/translate", config.endpoint.trim_end_matches('/'));
+    let body = LibreRequest {
+        q: query,
+        source: libre_language(&request.from),
+        target: libre_language(&request.to),
+        format: "text",
+        api_key: config.api_key.as_deref().filter(|k| !k.is_empty()),
+    };
+
+    let response = client.post(&url).json(&body).send().await?;
+    let status = response.status();
+    let body = response.bytes().await?;
+
+    // Error bodies are usually JSON with a message, but proxies may send anything
+    let parsed = serde_json::from_slice::<LibreResponse>(&body);
+    if let Some(error) = parsed.as_ref().ok().and_then(|r| r.error.as_ref()) {
+        return Err(KdError::Api(format!("LibreTranslate Error: {}", error)));
+    }
+    if !status.is_success() {
+        return Err(KdError::Api(format!(
+            "LibreTranslate Error: HTTP {}",
+            status
+        )));
+    }
+    let response = parsed?;
+
+    let mut result = QueryResult::new(query.to_string(), request.is_long_text);
+    result.source = QuerySource::Online(OnlineSource::LibreTranslate);
+
+    if let Some(text) = response.translated_text.filter(|t| !t.is_empty()) {
+        result.translations.push(text);
+        result.found = true;
+    }
+
+    Ok(result)
+}
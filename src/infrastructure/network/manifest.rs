@@ -0,0 +1,128 @@
+use crate::domain::error::KdError;
+use crate::infrastructure::network::mirror;
+use ed25519_dalek::{Signature, VerifyingKey};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// Description of a dictionary archive, published next to it as `<archive>.manifest.json`
+///
+/// The optional signature is an ed25519 signature (hex) over
+/// `"<version>\n<size>\n<sha256>"`, with the digest in lowercase hex.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Manifest {
+    pub version: String,
+    pub size: u64,
+    pub sha256: String,
+    pub signature: Option<String>,
+}
+
+impl Manifest {
+    fn signed_message(&self) -> String {
+        format!(
+            "{}\n{}\n{}",
+            self.version,
+            self.size,
+            self.sha256.to_lowercase()
+        )
+    }
+
+    /// Check the signature against `public_key` (hex)
+    pub fn verify_signature(&self, public_key: &str) -> Result<(), KdError> {
+        let key: [u8; 32] = decode_hex(public_key, "public key")?;
+        let key = VerifyingKey::from_bytes(&key)
+            .map_err(|e| KdError::Config(format!("Invalid update public key: {}", e)))?;
+
+        let signature = self
+            .signature
+            .as_deref()
+            .ok_or_else(|| KdError::Integrity("manifest is not signed".to_string()))?;
+        let signature: [u8; 64] = decode_hex(signature, "signature")
+            .map_err(|_| KdError::Integrity("malformed manifest signature".to_string()))?;
+
+        key.verify_strict(
+            self.signed_message().as_bytes(),
+            &Signature::from_bytes(&signature),
+        )
+        .map_err(|_| KdError::Integrity("manifest signature does not match".to_string()))
+    }
+
+    /// Check that the archive at `path` has the size and digest listed in the manifest
+    pub async fn verify_file(&self, path: &Path) -> Result<(), KdError> {
+        let path = path.to_path_buf();
+        let (size, digest) = tokio::task::spawn_blocking(move || hash_file(&path))
+            .await
+            .map_err(|e| KdError::Io(std::io::Error::other(format!("Task join error: {}", e))))??;
+
+        if size != self.size {
+            return Err(KdError::Integrity(format!(
+                "archive is {} bytes, manifest lists {}",
+                size, self.size
+            )));
+        }
+        if !digest.eq_ignore_ascii_case(self.sha256.trim()) {
+            return Err(KdError::Integrity(format!(
+                "archive SHA-256 is {}, manifest lists {}",
+                digest, self.sha256
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// URL of the manifest describing the archive at `archive_url`
+pub fn manifest_url(archive_url: &str) -> String {
+    format!("{}.manifest.json", archive_url)
+}
+
+/// Fetch the manifest of an archive, `None` when the source doesn't publish one
+pub async fn fetch(client: &Client, archive_url: &str) -> Result<Option<Manifest>, KdError> {
+    let url = manifest_url(archive_url);
+    let body = if let Some(path) = mirror::local_path(&url) {
+        match tokio::fs::read(&path).await {
+            Ok(body) => body,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+    } else {
+        let response = client
+            .get(url)
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        response.error_for_status()?.bytes().await?.to_vec()
+    };
+
+    let manifest = serde_json::from_slice(&body)
+        .map_err(|e| KdError::Integrity(format!("malformed manifest: {}", e)))?;
+    Ok(Some(manifest))
+}
+
+fn hash_file(path: &Path) -> Result<(u64, String), KdError> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+fn decode_hex<const N: usize>(text: &str, what: &str) -> Result<[u8; N], KdError> {
+    let bytes = hex::decode(text.trim())
+        .map_err(|e| KdError::Config(format!("Invalid {}: {}", what, e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| KdError::Config(format!("Invalid {}: expected {} bytes", what, N)))
+}
@@ -0,0 +1,97 @@
+use crate::domain::error::KdError;
+use crate::infrastructure::config::MirrorConfig;
+use futures_util::future::join_all;
+use reqwest::{Client, StatusCode, Url};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long a mirror may take to answer a latency probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct IPInfo {
+    country: String,
+}
+
+/// Local path of a `file://` URL, `None` for other schemes
+pub fn local_path(url: &str) -> Option<PathBuf> {
+    if !url.starts_with("file:") {
+        return None;
+    }
+    Url::parse(url).ok()?.to_file_path().ok()
+}
+
+/// Order mirrors by how they should be tried, according to `selection`
+pub async fn rank(
+    client: &Client,
+    mirrors: &[MirrorConfig],
+    selection: &str,
+) -> Result<Vec<MirrorConfig>, KdError> {
+    if mirrors.is_empty() {
+        return Err(KdError::Config(
+            "No mirror configured in [update]".to_string(),
+        ));
+    }
+
+    let mut mirrors = mirrors.to_vec();
+    match selection {
+        "latency" if mirrors.len() > 1 => {
+            let latencies = join_all(mirrors.iter().map(|m| probe(client, &m.url))).await;
+            let mut ranked: Vec<_> = latencies.into_iter().zip(mirrors).collect();
+            // Unreachable mirrors go last but stay as a fallback; the sort is stable
+            ranked.sort_by_key(|(latency, _)| latency.unwrap_or(Duration::MAX));
+            mirrors = ranked.into_iter().map(|(_, mirror)| mirror).collect();
+        }
+        // A single mirror has nothing to be compared with
+        "order" | "latency" => {}
+        "geolocation" => match detect_country(client).await {
+            Ok(country) => {
+                println!("Detected IP location {}.", country);
+                mirrors.sort_by_key(|m| {
+                    m.region
+                        .as_deref()
+                        .is_none_or(|region| !region.eq_ignore_ascii_case(&country))
+                });
+            }
+            Err(e) => println!(
+                "Failed to detect IP location ({}), using mirrors in configured order.",
+                e
+            ),
+        },
+        other => {
+            return Err(KdError::Config(format!(
+                "Unknown mirror selection {:?} (expected latency, order or geolocation)",
+                other
+            )))
+        }
+    }
+    Ok(mirrors)
+}
+
+/// Time a HEAD request to the mirror, `None` when it can't be reached or doesn't have the file
+async fn probe(client: &Client, url: &str) -> Option<Duration> {
+    if let Some(path) = local_path(url) {
+        return path.exists().then_some(Duration::ZERO);
+    }
+    let start = Instant::now();
+    let res = client.head(url).timeout(PROBE_TIMEOUT).send().await.ok()?;
+    // Some hosts refuse HEAD but would still serve the file
+    let status = res.status();
+    (status.is_success()
+        || status == StatusCode::METHOD_NOT_ALLOWED
+        || status == StatusCode::NOT_IMPLEMENTED)
+        .then(|| start.elapsed())
+}
+
+/// Detect the country of our IP address using ipinfo.io
+async fn detect_country(client: &Client) -> Result<String, KdError> {
+    let info: IPInfo = client
+        .get("https://ipinfo.io/json")
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?
+        .json()
+        .await?;
+    Ok(info.country.to_uppercase())
+}
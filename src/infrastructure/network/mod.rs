@@ -1,4 +1,11 @@
 // Network layer implementations
 
+pub mod bing;
 pub mod client;
+pub mod google;
 pub mod http;
+pub mod libretranslate;
+pub mod manifest;
+pub mod mirror;
+pub mod provider;
+pub mod template;
@@ -0,0 +1,126 @@
+// Provider registry: builds an ordered chain of online translators from config
+use crate::domain::error::KdError;
+use crate::domain::model::{QueryResult, QuerySource, TranslateRequest};
+use crate::domain::traits::Translator;
+use crate::infrastructure::config::Config;
+use crate::infrastructure::network::bing::BingTranslator;
+use crate::infrastructure::network::client::YoudaoTranslator;
+use crate::infrastructure::network::google::GoogleTranslator;
+use crate::infrastructure::network::libretranslate::LibreTranslator;
+use crate::infrastructure::network::template::HttpTemplateTranslator;
+use reqwest::Client;
+
+/// Ordered list of online translators, tried one after another
+pub struct ProviderChain {
+    providers: Vec<Box<dyn Translator>>,
+}
+
+impl ProviderChain {
+    /// Build the chain from the `providers` list, skipping disabled providers
+    pub fn from_config(client: &Client, config: &Config) -> Self {
+        Self::from_names(&config.providers, client, config)
+    }
+
+    /// Build the chain used for long text from the `long_text_providers` list
+    pub fn for_long_text(client: &Client, config: &Config) -> Self {
+        Self::from_names(&config.long_text_providers, client, config)
+    }
+
+    fn from_names(names: &[String], client: &Client, config: &Config) -> Self {
+        let providers = names
+            .iter()
+            .filter_map(|name| build_provider(name, client, config))
+            .collect();
+
+        Self { providers }
+    }
+
+    /// Names of the providers in the chain, in the order they are tried
+    pub fn names(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .map(|p| p.source().name().to_string())
+            .collect()
+    }
+
+    /// Query each provider in turn until one returns a result
+    ///
+    /// A provider that errors or finds nothing falls through to the next one.
+    /// If nobody finds the query, the first "not found" result is returned;
+    /// if every provider errors, the collected errors are reported together.
+    pub async fn translate(&self, request: &TranslateRequest) -> Result<QueryResult, KdError> {
+        if self.providers.is_empty() {
+            return Err(KdError::Config("No online provider enabled".to_string()));
+        }
+
+        let mut not_found = None;
+        let mut errors = Vec::new();
+
+        for provider in &self.providers {
+            let name = provider.source().name().to_string();
+            match provider.translate(request).await {
+                Ok(mut result) => {
+                    result.source = QuerySource::Online(provider.source());
+                    if result.found {
+                        return Ok(result);
+                    }
+                    tracing::debug!("Provider {} found nothing for {:?}", name, request.query);
+                    not_found.get_or_insert(result);
+                }
+                // An unconfigured provider is expected to be skipped, so keep it quiet
+                Err(e @ KdError::Config(_)) => {
+                    tracing::debug!("Provider {} skipped: {}", name, e);
+                    errors.push(format!("{}: {}", name, e));
+                }
+                Err(e) => {
+                    tracing::warn!("Provider {} failed: {}", name, e);
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+
+        match not_found {
+            Some(result) => Ok(result),
+            None => Err(KdError::Api(format!(
+                "All providers failed ({})",
+                errors.join("; ")
+            ))),
+        }
+    }
+}
+
+fn build_provider(name: &str, client: &Client, config: &Config) -> Option<Box<dyn Translator>> {
+    match name {
+        "youdao" if config.youdao.enable => Some(Box::new(YoudaoTranslator::new(
+            client.clone(),
+            config.clone(),
+        ))),
+        "youdao" => None,
+        "bing" if config.bing.enable => Some(Box::new(BingTranslator::new(
+            client.clone(),
+            config.bing.clone(),
+        ))),
+        "bing" => None,
+        "google" if config.google.enable => Some(Box::new(GoogleTranslator::new(
+            client.clone(),
+            config.google.clone(),
+        ))),
+        "google" => None,
+        "libretranslate" if config.libretranslate.enable => Some(Box::new(LibreTranslator::new(
+            client.clone(),
+            config.libretranslate.clone(),
+        ))),
+        "libretranslate" => None,
+        _ => match config.http_templates.iter().find(|t| t.name == name) {
+            Some(template) if template.enable => Some(Box::new(HttpTemplateTranslator::new(
+                client.clone(),
+                template.clone(),
+            ))),
+            Some(_) => None,
+            None => {
+                tracing::warn!("Unknown provider in config: {}", name);
+                None
+            }
+        },
+    }
+}
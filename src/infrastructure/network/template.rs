@@ -0,0 +1,298 @@
+// Generic HTTP JSON translation backend driven entirely by config
+use crate::domain::error::KdError;
+use crate::domain::model::{OnlineSource, QueryResult, QuerySource, TranslateRequest};
+use crate::domain::traits::Translator;
+use crate::infrastructure::config::HttpTemplateConfig;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+
+/// Translator that renders a request from a template and maps the JSON
+/// response into `QueryResult` with JSONPath-like expressions
+pub struct HttpTemplateTranslator {
+    client: Client,
+    config: HttpTemplateConfig,
+}
+
+impl HttpTemplateTranslator {
+    pub fn new(client: Client, config: HttpTemplateConfig) -> Self {
+        Self { client, config }
+    }
+}
+
+#[async_trait]
+impl Translator for HttpTemplateTranslator {
+    fn source(&self) -> OnlineSource {
+        OnlineSource::Custom(self.config.name.clone())
+    }
+
+    async fn translate(&self, request: &TranslateRequest) -> Result<QueryResult, KdError> {
+        query_template_impl(&self.client, &self.config, request).await
+    }
+}
+
+async fn query_template_impl(
+    client: &Client,
+    config: &HttpTemplateConfig,
+    request: &TranslateRequest,
+) -> Result<QueryResult, KdError> {
+    let query = request.query.as_str();
+    if config.url.is_empty() {
+        return Err(KdError::Config(format!(
+            "HTTP template {} has no url",
+            config.name
+        )));
+    }
+
+    let vars = [
+        ("query", query),
+        ("from", request.from.as_str()),
+        ("to", request.to.as_str()),
+    ];
+    let url = render(&config.url, &vars, percent_encode);
+
+    let mut builder = match config.method.to_uppercase().as_str() {
+        "GET" => client.get(&url),
+        "POST" => client.post(&url),
+        other => {
+            return Err(KdError::Config(format!(
+                "HTTP template {}: unsupported method {}",
+                config.name, other
+            )))
+        }
+    };
+    for (name, value) in &config.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = &config.body {
+        builder = builder
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(render(body, &vars, json_escape));
+    }
+
+    let response = builder.send().await?;
+    if !response.status().is_success() {
+        return Err(KdError::Api(format!(
+            "HTTP template {} Error: HTTP {}",
+            config.name,
+            response.status()
+        )));
+    }
+    let json = response.json::<Value>().await?;
+
+    let fields = &config.fields;
+    let first = |path: &Option<String>| -> Result<Option<String>, KdError> {
+        Ok(match path {
+            Some(p) => select_strings(&json, p)?.into_iter().next(),
+            None => None,
+        })
+    };
+
+    let mut result = QueryResult::new(query.to_string(), request.is_long_text);
+    result.source = QuerySource::Online(OnlineSource::Custom(config.name.clone()));
+    if let Some(path) = &fields.translations {
+        result.translations = select_strings(&json, path)?;
+    }
+    result.pronunciation_us = first(&fields.pronunciation_us)?;
+    result.pronunciation_uk = first(&fields.pronunciation_uk)?;
+    result.pronunciation = first(&fields.pronunciation)?
+        .or_else(|| result.pronunciation_us.clone())
+        .or_else(|| result.pronunciation_uk.clone());
+    if let (Some(orig), Some(trans)) = (&fields.examples_original, &fields.examples_translation) {
+        result.examples = select_strings(&json, orig)?
+            .into_iter()
+            .zip(select_strings(&json, trans)?)
+            .collect();
+    }
+
+    result.found = !result.translations.is_empty();
+
+    Ok(result)
+}
+
+/// Replace `{{name}}` placeholders, escaping each value for its context
+///
+/// The template is scanned once, so a value containing a placeholder is left as is.
+/// Unknown placeholders are kept verbatim.
+fn render(template: &str, vars: &[(&str, &str)], escape: fn(&str) -> String) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after.find("}}").and_then(|end| {
+            let name = &after[..end];
+            let (_, value) = vars.iter().find(|(var, _)| *var == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(&escape(value));
+                rest = &after[end + 2..];
+            }
+            None => {
+                rendered.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Escape a value for use inside a JSON string literal
+fn json_escape(value: &str) -> String {
+    let quoted = Value::String(value.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Percent-encode a value for use in a URL
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Evaluate a JSONPath-like expression and collect the string values it selects
+///
+/// Supported syntax: `$` for the root, `.key` for object members, `[n]` for
+/// array indices, `['key']` for keys containing dots and `[*]` / `.*` for
+/// every element. Selected arrays are flattened and numbers are converted to
+/// strings.
+fn select_strings(root: &Value, path: &str) -> Result<Vec<String>, KdError> {
+    let segments = parse_path(path).map_err(KdError::Config)?;
+    let mut nodes = vec![root];
+
+    for segment in segments {
+        nodes = nodes
+            .into_iter()
+            .flat_map(|node| -> Vec<&Value> {
+                match (&segment, node) {
+                    (Segment::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+                    (Segment::Index(i), Value::Array(items)) => items.get(*i).into_iter().collect(),
+                    (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+                    (Segment::Wildcard, Value::Object(map)) => map.values().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+
+    let mut out = Vec::new();
+    for node in nodes {
+        collect_strings(node, &mut out);
+    }
+    Ok(out)
+}
+
+fn collect_strings(node: &Value, out: &mut Vec<String>) {
+    match node {
+        Value::String(s) if !s.is_empty() => out.push(s.clone()),
+        Value::Number(n) => out.push(n.to_string()),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        _ => {}
+    }
+}
+
+enum Segment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// Check that every field expression of a template parses
+pub fn validate(config: &HttpTemplateConfig) -> Result<(), KdError> {
+    let fields = &config.fields;
+    let paths = [
+        &fields.translations,
+        &fields.pronunciation,
+        &fields.pronunciation_us,
+        &fields.pronunciation_uk,
+        &fields.examples_original,
+        &fields.examples_translation,
+    ];
+    for path in paths.into_iter().flatten() {
+        parse_path(path)
+            .map_err(|e| KdError::Config(format!("HTTP template {}: {}", config.name, e)))?;
+    }
+    Ok(())
+}
+
+/// Split an expression into segments, e.g. `$.data['a.b'][0].*`
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let invalid = |reason: &str| format!("invalid path {:?}: {}", path, reason);
+    let trimmed = path.trim();
+    let mut chars = trimmed
+        .strip_prefix('$')
+        .unwrap_or(trimmed)
+        .chars()
+        .peekable();
+    let mut segments = Vec::new();
+
+    // A bare leading key is allowed, as in `data.items`
+    if chars.peek().is_some_and(|c| *c != '.' && *c != '[') {
+        segments.push(parse_key(&mut chars).ok_or_else(|| invalid("empty key"))?);
+    }
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => segments.push(parse_key(&mut chars).ok_or_else(|| invalid("empty key"))?),
+            '[' => {
+                let segment = match chars.peek() {
+                    Some(&quote @ ('\'' | '"')) => {
+                        chars.next();
+                        let key: String = chars.by_ref().take_while(|c| *c != quote).collect();
+                        if chars.next() != Some(']') {
+                            return Err(invalid("unterminated quoted key"));
+                        }
+                        Segment::Key(key)
+                    }
+                    _ => {
+                        let mut inner = String::new();
+                        loop {
+                            match chars.next() {
+                                Some(']') => break,
+                                Some(c) => inner.push(c),
+                                None => return Err(invalid("missing ']'")),
+                            }
+                        }
+                        match inner.trim() {
+                            "" => return Err(invalid("empty brackets")),
+                            "*" => Segment::Wildcard,
+                            inner => match inner.parse() {
+                                Ok(i) => Segment::Index(i),
+                                Err(_) => Segment::Key(inner.to_string()),
+                            },
+                        }
+                    }
+                };
+                segments.push(segment);
+            }
+            other => return Err(invalid(&format!("unexpected {:?}", other))),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Read a dotted key up to the next `.` or `[`; `*` selects every member
+fn parse_key(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Segment> {
+    let mut key = String::new();
+    while let Some(&c) = chars.peek() {
+        if matches!(c, '.' | '[' | ']') {
+            break;
+        }
+        key.push(c);
+        chars.next();
+    }
+    match key.as_str() {
+        "" => None,
+        "*" => Some(Segment::Wildcard),
+        _ => Some(Segment::Key(key)),
+    }
+}
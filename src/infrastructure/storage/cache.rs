@@ -1,47 +1,166 @@
-// In-memory cache implementation using DashMap
-use crate::domain::model::QueryResult;
-use dashmap::DashMap;
+// In-memory LRU cache in front of the database
+use crate::domain::model::{MemoryCacheStats, QueryResult};
+use crate::domain::traits::Cache;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 
-/// Thread-safe in-memory cache
+/// Rough per-entry bookkeeping cost on top of the strings it holds
+const ENTRY_OVERHEAD: usize = 256;
+
+/// Thread-safe in-memory cache bounded by entry count and by estimated size
 ///
-/// Reserved for future use with Cache trait abstraction.
-/// Currently using DashMap directly in AppState for simplicity.
-#[allow(dead_code)]
+/// The least recently used entries are evicted first. A limit of 0 disables
+/// that bound.
 pub struct MemoryCache {
-    map: DashMap<String, QueryResult>,
+    inner: Mutex<Lru>,
+    max_entries: usize,
+    max_bytes: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<String, Slot>,
+    /// Keys by the tick of their last use, oldest first
+    order: BTreeMap<u64, String>,
+    tick: u64,
+    bytes: usize,
+}
+
+struct Slot {
+    value: QueryResult,
+    size: usize,
+    tick: u64,
+}
+
+impl Lru {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Slot> {
+        let slot = self.entries.remove(key)?;
+        self.order.remove(&slot.tick);
+        self.bytes -= slot.size;
+        Some(slot)
+    }
 }
 
 impl MemoryCache {
-    #[allow(dead_code)]
-    pub fn new() -> Self {
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
         Self {
-            map: DashMap::new(),
+            inner: Mutex::new(Lru::default()),
+            max_entries,
+            max_bytes,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
 
-    #[allow(dead_code)]
-    pub fn get(&self, key: &str) -> Option<QueryResult> {
-        self.map.get(key).map(|entry| entry.value().clone())
+    fn lock(&self) -> std::sync::MutexGuard<'_, Lru> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn over_limits(&self, lru: &Lru) -> bool {
+        (self.max_entries > 0 && lru.entries.len() > self.max_entries)
+            || (self.max_bytes > 0 && lru.bytes > self.max_bytes)
     }
+}
 
-    #[allow(dead_code)]
-    pub fn insert(&self, key: String, value: QueryResult) {
-        self.map.insert(key, value);
+impl Cache for MemoryCache {
+    fn get(&self, key: &str) -> Option<QueryResult> {
+        let mut lru = self.lock();
+        let tick = lru.next_tick();
+        let Some(slot) = lru.entries.get_mut(key) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        let last_used = std::mem::replace(&mut slot.tick, tick);
+        let value = slot.value.clone();
+        lru.order.remove(&last_used);
+        lru.order.insert(tick, key.to_string());
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(value)
     }
 
-    #[allow(dead_code)]
-    pub fn clear(&self) {
-        self.map.clear();
+    fn insert(&self, key: String, value: QueryResult) {
+        let size = estimated_size(&key, &value);
+        let mut lru = self.lock();
+        lru.remove(&key);
+        // An entry bigger than the whole cache would only flush everything else
+        if self.max_bytes > 0 && size > self.max_bytes {
+            return;
+        }
+
+        let tick = lru.next_tick();
+        lru.order.insert(tick, key.clone());
+        lru.entries.insert(key, Slot { value, size, tick });
+        lru.bytes += size;
+
+        while self.over_limits(&lru) {
+            let Some((_, oldest)) = lru.order.pop_first() else {
+                break;
+            };
+            if let Some(slot) = lru.entries.remove(&oldest) {
+                lru.bytes -= slot.size;
+            }
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn len(&self) -> usize {
-        self.map.len()
+    fn clear(&self) {
+        let mut lru = self.lock();
+        lru.entries.clear();
+        lru.order.clear();
+        lru.bytes = 0;
     }
-}
 
-impl Default for MemoryCache {
-    fn default() -> Self {
-        Self::new()
+    fn len(&self) -> usize {
+        self.lock().entries.len()
     }
+
+    fn stats(&self) -> MemoryCacheStats {
+        let lru = self.lock();
+        MemoryCacheStats {
+            entries: lru.entries.len(),
+            bytes: lru.bytes,
+            max_entries: self.max_entries,
+            max_bytes: self.max_bytes,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Approximate heap footprint of an entry: its strings plus a fixed overhead
+fn estimated_size(key: &str, value: &QueryResult) -> usize {
+    let pairs = |pairs: &[(String, String)]| -> usize {
+        pairs.iter().map(|(a, b)| a.len() + b.len()).sum()
+    };
+    let optional = |text: &Option<String>| text.as_ref().map_or(0, String::len);
+
+    ENTRY_OVERHEAD
+        + key.len()
+        + value.query.len()
+        + optional(&value.pronunciation)
+        + optional(&value.pronunciation_us)
+        + optional(&value.pronunciation_uk)
+        + optional(&value.collins_rank)
+        + optional(&value.headword)
+        + value.translations.iter().map(String::len).sum::<usize>()
+        + value.suggestions.iter().map(String::len).sum::<usize>()
+        + pairs(&value.examples)
+        + value
+            .collins_items
+            .iter()
+            .map(|item| {
+                optional(&item.additional) + optional(&item.major_trans) + pairs(&item.examples)
+            })
+            .sum::<usize>()
 }
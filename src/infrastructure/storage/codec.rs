@@ -0,0 +1,168 @@
+// Encoding of QueryResult blobs stored in the cache table
+use crate::domain::model::QueryResult;
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::sync::{Arc, LazyLock, RwLock};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+use zstd::stream::{decode_all, encode_all};
+use zstd::zstd_safe;
+
+/// zstd level for cache blobs (0 picks zstd's default)
+const LEVEL: i32 = 0;
+
+/// Leading byte of a binary-encoded result, followed by its postcard encoding
+///
+/// postcard is positional, so any change to the fields of `QueryResult` (or
+/// the types inside it) needs a new version and a decoder for the old layout.
+/// Rows written before the binary format hold JSON, which starts with `{`.
+const FORMAT_VERSION: u8 = 1;
+
+/// How a stored result was serialized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Written before the binary format; rewritten when next read
+    Json,
+    Binary,
+}
+
+/// Trained dictionaries, loaded from the database when it's opened
+///
+/// Held process-wide because blobs are encoded and decoded inside database
+/// calls that only see a connection. Every frame names the dictionary it was
+/// compressed with, so rows written before one existed still decode.
+static DICTIONARIES: LazyLock<RwLock<Dictionaries>> = LazyLock::new(Default::default);
+
+#[derive(Default)]
+struct Dictionaries {
+    /// The latest dictionary, used for every blob written from now on
+    current: Option<Arc<EncoderDictionary<'static>>>,
+    decoders: HashMap<u32, Arc<DecoderDictionary<'static>>>,
+}
+
+/// A serialized, compressed cache entry ready to be written
+pub struct EncodedEntry {
+    pub data: Vec<u8>,
+    pub compressed_size: usize,
+    pub original_size: usize,
+}
+
+pub fn encode(result: &QueryResult) -> std::io::Result<EncodedEntry> {
+    let serialized = serialize(result)?;
+    let current = DICTIONARIES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .current
+        .clone();
+    compress(serialized, current.as_deref())
+}
+
+pub fn decode(data: &[u8]) -> std::io::Result<QueryResult> {
+    decode_with_format(data).map(|(result, _)| result)
+}
+
+/// Decode a stored blob and report how it was serialized
+pub fn decode_with_format(data: &[u8]) -> std::io::Result<(QueryResult, Format)> {
+    deserialize(&decompress(data)?)
+}
+
+/// Binary encoding of a result, before compression
+pub fn serialize(result: &QueryResult) -> std::io::Result<Vec<u8>> {
+    let mut serialized = vec![FORMAT_VERSION];
+    postcard::to_io(result, &mut serialized).map_err(invalid_data)?;
+    Ok(serialized)
+}
+
+fn deserialize(serialized: &[u8]) -> std::io::Result<(QueryResult, Format)> {
+    match serialized.first() {
+        Some(&FORMAT_VERSION) => {
+            let result = postcard::from_bytes(&serialized[1..]).map_err(invalid_data)?;
+            Ok((result, Format::Binary))
+        }
+        Some(b'{') => Ok((serde_json::from_slice(serialized)?, Format::Json)),
+        Some(version) => Err(invalid_data(format!(
+            "unknown result format version {}",
+            version
+        ))),
+        None => Err(invalid_data("empty result")),
+    }
+}
+
+fn invalid_data(e: impl ToString) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// Decompress a stored blob back to its serialized form
+fn decompress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let Some(dict_id) = zstd_safe::get_dict_id_from_frame(data) else {
+        return decode_all(Cursor::new(data));
+    };
+    let dictionary = DICTIONARIES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .decoders
+        .get(&dict_id.get())
+        .cloned()
+        .ok_or_else(|| {
+            invalid_data(format!(
+                "blob was compressed with unknown dictionary {}",
+                dict_id
+            ))
+        })?;
+
+    let mut decompressed = Vec::new();
+    zstd::stream::read::Decoder::with_prepared_dictionary(data, &dictionary)?
+        .read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Compress a stored blob again with a newly trained dictionary, in the binary format
+pub fn recompress(data: &[u8], dictionary: &EncoderDictionary) -> std::io::Result<EncodedEntry> {
+    compress(serialize(&decode(data)?)?, Some(dictionary))
+}
+
+fn compress(
+    serialized: Vec<u8>,
+    dictionary: Option<&EncoderDictionary>,
+) -> std::io::Result<EncodedEntry> {
+    let data = match dictionary {
+        Some(dictionary) => {
+            zstd::bulk::Compressor::with_prepared_dictionary(dictionary)?.compress(&serialized)?
+        }
+        None => encode_all(Cursor::new(&serialized), LEVEL)?,
+    };
+
+    Ok(EncodedEntry {
+        compressed_size: data.len(),
+        original_size: serialized.len(),
+        data,
+    })
+}
+
+/// Prepare a trained dictionary for compressing blobs
+pub fn encoder_dictionary(dictionary: &[u8]) -> EncoderDictionary<'static> {
+    EncoderDictionary::copy(dictionary, LEVEL)
+}
+
+/// ID a trained dictionary writes into the frames it compresses
+pub fn dictionary_id(dictionary: &[u8]) -> Option<u32> {
+    zstd_safe::get_dict_id_from_dict(dictionary).map(|id| id.get())
+}
+
+/// Make a dictionary available for decoding; the `current` one also encodes new blobs
+pub fn register_dictionary(dictionary: &[u8], current: bool) {
+    let Some(dict_id) = dictionary_id(dictionary) else {
+        return;
+    };
+    let mut dictionaries = DICTIONARIES.write().unwrap_or_else(|e| e.into_inner());
+    dictionaries
+        .decoders
+        .insert(dict_id, Arc::new(DecoderDictionary::copy(dictionary)));
+    if current {
+        dictionaries.current = Some(Arc::new(encoder_dictionary(dictionary)));
+    }
+}
+
+/// Wrap a codec error so it can be returned from inside a database call
+pub fn blob_error(e: std::io::Error) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(e))
+}
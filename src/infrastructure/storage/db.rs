@@ -1,31 +1,93 @@
+use crate::domain::chinese::contains_cjk;
 use crate::domain::error::KdError;
-use crate::domain::model::QueryResult;
+use crate::domain::model::{HistoryEntry, LanguagePair, QueryResult, QuerySource};
 use crate::domain::traits::Database;
+use crate::infrastructure::storage::codec::{self, EncodedEntry, Format};
+use crate::infrastructure::storage::{dictionary, pinyin, search};
 use async_trait::async_trait;
 use std::path::Path;
 use tokio_rusqlite::Connection;
 
+/// Namespace of English headwords (en → zh entries)
+pub const EN_NAMESPACE: &str = "en";
+/// Namespace of Chinese headwords (zh → en entries)
+pub const ZH_NAMESPACE: &str = "zh";
+
+/// Namespace a lookup is cached in
+///
+/// English ↔ Chinese share the offline dictionary's namespaces; any other
+/// direction gets its own, named after the pair ("en-ja").
+pub fn namespace_for(pair: &LanguagePair) -> String {
+    match (pair.from.as_str(), pair.to.as_str()) {
+        ("en" | "auto", "zh") => EN_NAMESPACE.to_string(),
+        ("zh", "en") => ZH_NAMESPACE.to_string(),
+        (from, to) => format!("{}-{}", from, to),
+    }
+}
+
+/// Source language of the queries stored in a namespace
+pub fn source_language(ns: &str) -> &str {
+    match ns {
+        EN_NAMESPACE => "en",
+        ZH_NAMESPACE => "zh",
+        pair => pair.split('-').next().unwrap_or(pair),
+    }
+}
+
+const CREATE_CACHE: &str = "CREATE TABLE IF NOT EXISTS cache (
+    ns TEXT NOT NULL DEFAULT 'en',
+    query TEXT NOT NULL,
+    data BLOB NOT NULL,
+    compressed_size INTEGER NOT NULL,
+    original_size INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    source TEXT NOT NULL DEFAULT 'offline',
+    PRIMARY KEY (ns, query)
+)";
+
+/// `source` of rows that came with the offline dictionary
+pub const OFFLINE_SOURCE: &str = "offline";
+
 pub async fn init_database(db_path: &Path) -> Result<Connection, KdError> {
     let db = Connection::open(db_path.to_path_buf()).await?;
 
     db.call(|conn| {
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS cache (
-                query TEXT PRIMARY KEY,
-                data BLOB NOT NULL,
-                compressed_size INTEGER NOT NULL,
-                original_size INTEGER NOT NULL,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL
+            "CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
             )",
             [],
         )?;
 
+        // Dictionaries first: the column migrations below decode existing rows
+        dictionary::create_table(conn)?;
+        dictionary::load(conn)?;
+
+        conn.execute(CREATE_CACHE, [])?;
+        add_source_column(conn)?;
+        add_namespace_column(conn)?;
+
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_cache_updated ON cache(updated_at)",
             [],
         )?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                query TEXT NOT NULL,
+                found INTEGER NOT NULL,
+                source TEXT NOT NULL,
+                queried_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        search::create_index(conn)?;
+        pinyin::create_index(conn)?;
+
         Ok(())
     })
     .await?;
@@ -33,6 +95,103 @@ pub async fn init_database(db_path: &Path) -> Result<Connection, KdError> {
     Ok(db)
 }
 
+/// Rows decoded per batch when filling in the source column
+const SOURCE_BATCH: i64 = 10_000;
+
+/// Record where each row came from, so online results can expire and be pruned
+///
+/// Older tables don't say; the source is read back from each stored result,
+/// a batch of rows at a time.
+fn add_source_column(conn: &mut rusqlite::Connection) -> rusqlite::Result<()> {
+    let has_source = conn
+        .prepare("SELECT 1 FROM pragma_table_info('cache') WHERE name = 'source'")?
+        .exists([])?;
+    if has_source {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        "ALTER TABLE cache ADD COLUMN source TEXT NOT NULL DEFAULT 'offline'",
+        [],
+    )?;
+    let total: i64 = tx.query_row("SELECT COUNT(*) FROM cache", [], |row| row.get(0))?;
+    if total > 0 {
+        tracing::info!("Reading the source of {} cached entries", total);
+    }
+    {
+        let mut select =
+            tx.prepare("SELECT rowid, data FROM cache WHERE rowid > ? ORDER BY rowid LIMIT ?")?;
+        let mut update = tx.prepare("UPDATE cache SET source = ? WHERE rowid = ?")?;
+        let mut last = i64::MIN;
+        loop {
+            let batch: Vec<(i64, Vec<u8>)> = select
+                .query_map(rusqlite::params![last, SOURCE_BATCH], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?
+                .collect::<Result<_, _>>()?;
+            let Some((rowid, _)) = batch.last() else {
+                break;
+            };
+            last = *rowid;
+            for (rowid, data) in &batch {
+                if let Ok(source @ QuerySource::Online(_)) = codec::decode(data).map(|r| r.source) {
+                    update.execute(rusqlite::params![source.name(), rowid])?;
+                }
+            }
+        }
+    }
+
+    tx.commit()
+}
+
+/// Rebuild a cache table from before namespaces, keyed by query alone
+///
+/// Rowids are kept so the search index stays valid. Chinese headwords move
+/// to the "zh" namespace, everything else to "en".
+fn add_namespace_column(conn: &mut rusqlite::Connection) -> rusqlite::Result<()> {
+    let has_ns = conn
+        .prepare("SELECT 1 FROM pragma_table_info('cache') WHERE name = 'ns'")?
+        .exists([])?;
+    if has_ns {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    // Drop the trigger first so dropping the old table leaves the search index alone
+    tx.execute_batch(&format!(
+        "DROP TRIGGER IF EXISTS cache_fts_delete;
+         ALTER TABLE cache RENAME TO cache_old;
+         {};
+         INSERT INTO cache (rowid, ns, query, data, compressed_size, original_size, created_at, updated_at, source)
+             SELECT rowid, 'en', query, data, compressed_size, original_size, created_at, updated_at, source
+             FROM cache_old;
+         DROP TABLE cache_old;",
+        CREATE_CACHE
+    ))?;
+
+    let chinese: Vec<i64> = {
+        let mut stmt = tx.prepare("SELECT rowid, query FROM cache")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        rows.filter_map(|row| match row {
+            Ok((rowid, query)) if contains_cjk(&query) => Some(Ok(rowid)),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect::<Result<_, _>>()?
+    };
+    for rowid in chinese {
+        tx.execute(
+            "UPDATE cache SET ns = ? WHERE rowid = ?",
+            rusqlite::params![ZH_NAMESPACE, rowid],
+        )?;
+    }
+
+    tx.commit()
+}
+
 /// SQLite database implementation
 ///
 /// Reserved for future use with Database trait abstraction.
@@ -50,160 +209,408 @@ impl SqliteDatabase {
 
 #[async_trait]
 impl Database for SqliteDatabase {
-    async fn query_cache(&self, query: &str) -> Result<Option<QueryResult>, KdError> {
-        query_cache_impl(&self.conn, query).await
+    async fn query_cache(&self, ns: &str, query: &str) -> Result<Option<QueryResult>, KdError> {
+        query_cache_impl(&self.conn, ns, query).await
     }
 
-    async fn insert_cache(&self, query: &str, result: &QueryResult) -> Result<(), KdError> {
-        insert_cache_impl(&self.conn, query, result).await
+    async fn insert_cache(
+        &self,
+        ns: &str,
+        query: &str,
+        result: &QueryResult,
+    ) -> Result<(), KdError> {
+        insert_cache_impl(&self.conn, ns, query, result).await
     }
 
     async fn batch_insert_cache(
         &self,
+        ns: &str,
         items: Vec<(String, QueryResult)>,
     ) -> Result<usize, KdError> {
-        batch_insert_cache_impl(&self.conn, items).await
+        batch_insert_cache_impl(&self.conn, ns, items).await
     }
 }
 
 // Internal implementation functions (kept for backward compatibility)
-pub async fn query_cache(db: &Connection, query: &str) -> Result<Option<QueryResult>, KdError> {
-    query_cache_impl(db, query).await
+pub async fn query_cache(
+    db: &Connection,
+    ns: &str,
+    query: &str,
+) -> Result<Option<QueryResult>, KdError> {
+    query_cache_impl(db, ns, query).await
 }
 
 pub async fn insert_cache(
     db: &Connection,
+    ns: &str,
     query: &str,
     result: &QueryResult,
 ) -> Result<(), KdError> {
-    insert_cache_impl(db, query, result).await
+    insert_cache_impl(db, ns, query, result).await
 }
 
-pub async fn batch_insert_cache(
+/// Insert rows whose results were already encoded, in a single transaction
+///
+/// Lets callers do the encoding on their own threads, away from the database.
+pub async fn insert_encoded_batch(
     db: &Connection,
-    items: Vec<(String, QueryResult)>,
+    ns: &str,
+    items: Vec<(String, QueryResult, EncodedEntry)>,
 ) -> Result<usize, KdError> {
-    batch_insert_cache_impl(db, items).await
+    insert_encoded_batch_impl(db, ns, items).await
 }
 
-// Internal implementation
-async fn query_cache_impl(db: &Connection, query: &str) -> Result<Option<QueryResult>, KdError> {
+pub async fn count_cache(db: &Connection) -> Result<usize, KdError> {
+    let count: i64 = db
+        .call(|conn| conn.query_row("SELECT COUNT(*) FROM cache", [], |row| row.get(0)))
+        .await?;
+
+    Ok(count as usize)
+}
+
+/// Read a value from the meta table
+pub async fn get_meta(db: &Connection, key: &str) -> Result<Option<String>, KdError> {
     use rusqlite::OptionalExtension;
-    use std::io::Cursor;
     use tokio_rusqlite::params;
-    use zstd::stream::decode_all;
 
-    let query_string = query.to_string();
-    let result = db
+    let key = key.to_string();
+    let value = db
         .call(move |conn| {
             conn.query_row(
-                "SELECT data FROM cache WHERE query = ?",
-                params![query_string],
-                |row| {
-                    let compressed_data: Vec<u8> = row.get(0)?;
-                    let decompressed = decode_all(Cursor::new(&compressed_data)).map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Blob,
-                            Box::new(e),
-                        )
-                    })?;
-                    let result: QueryResult =
-                        serde_json::from_slice(&decompressed).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Blob,
-                                Box::new(e),
-                            )
-                        })?;
-                    Ok(result)
-                },
+                "SELECT value FROM meta WHERE key = ?",
+                params![key],
+                |row| row.get(0),
             )
             .optional()
         })
         .await?;
 
+    Ok(value)
+}
+
+/// Write a value to the meta table
+pub async fn set_meta(db: &Connection, key: &str, value: &str) -> Result<(), KdError> {
+    use tokio_rusqlite::params;
+
+    let key = key.to_string();
+    let value = value.to_string();
+    db.call(move |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?, ?)",
+            params![key, value],
+        )
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Whether a row is stored under exactly `query`
+pub async fn has_entry(db: &Connection, ns: &str, query: &str) -> Result<bool, KdError> {
+    use tokio_rusqlite::params;
+
+    let (ns, query) = (ns.to_string(), query.to_string());
+    let found = db
+        .call(move |conn| {
+            conn.prepare("SELECT 1 FROM cache WHERE ns = ? AND query = ?")?
+                .exists(params![ns, query])
+        })
+        .await?;
+
+    Ok(found)
+}
+
+/// Every headword in a namespace
+pub async fn all_headwords(db: &Connection, ns: &str) -> Result<Vec<String>, KdError> {
+    use tokio_rusqlite::params;
+
+    let ns = ns.to_string();
+    let words = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare("SELECT query FROM cache WHERE ns = ?")?;
+            let rows = stmt.query_map(params![ns], |row| row.get(0))?;
+            rows.collect::<Result<Vec<String>, _>>()
+        })
+        .await?;
+
+    Ok(words)
+}
+
+/// Headwords that could be within `tolerance` edits of the ASCII `word`
+///
+/// Narrowed through the primary key to headwords sharing the first letter
+/// (in either case), with a length no further than `tolerance` from the word's.
+pub async fn spelling_candidates(
+    db: &Connection,
+    ns: &str,
+    word: &str,
+    tolerance: usize,
+) -> Result<Vec<String>, KdError> {
+    use tokio_rusqlite::params;
+
+    let Some(first) = word.bytes().next() else {
+        return Ok(Vec::new());
+    };
+    let range = |c: u8| ((c as char).to_string(), ((c + 1) as char).to_string());
+    let (lower, upper) = (
+        range(first.to_ascii_lowercase()),
+        range(first.to_ascii_uppercase()),
+    );
+    let length = word.len();
+    let (shortest, longest) = (
+        length.saturating_sub(tolerance) as i64,
+        (length + tolerance) as i64,
+    );
+
+    let ns = ns.to_string();
+    let words = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT query FROM cache
+                 WHERE ns = ?1
+                   AND ((query >= ?2 AND query < ?3) OR (query >= ?4 AND query < ?5))
+                   AND length(query) BETWEEN ?6 AND ?7",
+            )?;
+            let rows = stmt.query_map(
+                params![ns, lower.0, lower.1, upper.0, upper.1, shortest, longest],
+                |row| row.get(0),
+            )?;
+            rows.collect::<Result<Vec<String>, _>>()
+        })
+        .await?;
+
+    Ok(words)
+}
+
+/// Record a lookup in the query history, keeping only the newest `limit` rows
+pub async fn insert_history(
+    db: &Connection,
+    entry: HistoryEntry,
+    limit: usize,
+) -> Result<(), KdError> {
+    use tokio_rusqlite::params;
+
+    db.call(move |conn| {
+        conn.execute(
+            "INSERT INTO history (query, found, source, queried_at) VALUES (?, ?, ?, ?)",
+            params![entry.query, entry.found, entry.source, entry.queried_at],
+        )?;
+        conn.execute(
+            "DELETE FROM history WHERE id <= last_insert_rowid() - ?",
+            params![limit as i64],
+        )
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Most recent lookups, newest first
+pub async fn recent_history(db: &Connection, limit: usize) -> Result<Vec<HistoryEntry>, KdError> {
+    use tokio_rusqlite::params;
+
+    let entries = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT query, found, source, queried_at FROM history ORDER BY id DESC LIMIT ?",
+            )?;
+            let rows = stmt.query_map(params![limit as i64], |row| {
+                Ok(HistoryEntry {
+                    query: row.get(0)?,
+                    found: row.get(1)?,
+                    source: row.get(2)?,
+                    queried_at: row.get(3)?,
+                })
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
+
+    Ok(entries)
+}
+
+// Internal implementation
+async fn query_cache_impl(
+    db: &Connection,
+    ns: &str,
+    query: &str,
+) -> Result<Option<QueryResult>, KdError> {
+    use rusqlite::OptionalExtension;
+    use tokio_rusqlite::params;
+
+    let ns = ns.to_string();
+    let query_string = query.to_string();
+    let result = db
+        .call(move |conn| {
+            let row = conn
+                .query_row(
+                    "SELECT rowid, data FROM cache WHERE ns = ? AND query = ?",
+                    params![ns, query_string],
+                    |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?)),
+                )
+                .optional()?;
+            let Some((rowid, data)) = row else {
+                return Ok(None);
+            };
+
+            let (result, format) = codec::decode_with_format(&data).map_err(codec::blob_error)?;
+            if format == Format::Json {
+                rewrite_binary(conn, rowid, &result)?;
+            }
+            Ok(Some(result))
+        })
+        .await?;
+
     Ok(result)
 }
 
+/// Store a row read in the old JSON format again in the binary one
+///
+/// Its timestamps are left alone: the content hasn't changed.
+fn rewrite_binary(
+    conn: &rusqlite::Connection,
+    rowid: i64,
+    result: &QueryResult,
+) -> rusqlite::Result<()> {
+    let encoded = codec::encode(result).map_err(codec::blob_error)?;
+    conn.execute(
+        "UPDATE cache SET data = ?, compressed_size = ?, original_size = ? WHERE rowid = ?",
+        rusqlite::params![
+            encoded.data,
+            encoded.compressed_size,
+            encoded.original_size,
+            rowid
+        ],
+    )?;
+    Ok(())
+}
+
+/// Insert or update a row, keeping its rowid so the search indexes stay valid
+const UPSERT_CACHE: &str =
+    "INSERT INTO cache (ns, query, data, compressed_size, original_size, created_at, updated_at, source)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+     ON CONFLICT(ns, query) DO UPDATE SET
+         data = excluded.data,
+         compressed_size = excluded.compressed_size,
+         original_size = excluded.original_size,
+         updated_at = excluded.updated_at,
+         source = excluded.source
+     RETURNING rowid";
+
+/// Value of the `source` column for a result
+fn row_source(result: &QueryResult) -> &str {
+    match &result.source {
+        QuerySource::Online(_) => result.source.name(),
+        _ => OFFLINE_SOURCE,
+    }
+}
+
 async fn insert_cache_impl(
     db: &Connection,
+    ns: &str,
     query: &str,
     result: &QueryResult,
 ) -> Result<(), KdError> {
-    use std::io::Cursor;
     use tokio_rusqlite::params;
-    use zstd::stream::encode_all;
 
-    let serialized = serde_json::to_vec(result)?;
-    let compressed = encode_all(Cursor::new(&serialized), 0)?;
+    let encoded = codec::encode(result)?;
     let now = chrono::Utc::now().timestamp();
 
+    let ns = ns.to_string();
     let query_string = query.to_string();
-    let compressed_len = compressed.len();
-    let original_len = serialized.len();
+    let result = result.clone();
+    let source = row_source(&result).to_string();
 
     db.call(move |conn| {
-        conn.execute(
-            "INSERT OR REPLACE INTO cache (query, data, compressed_size, original_size, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?)",
+        let tx = conn.transaction()?;
+        let rowid: i64 = tx.query_row(
+            UPSERT_CACHE,
             params![
+                ns,
                 query_string,
-                compressed,
-                compressed_len,
-                original_len,
+                encoded.data,
+                encoded.compressed_size,
+                encoded.original_size,
+                now,
                 now,
-                now
+                source
             ],
-        )
-    }).await?;
+            |row| row.get(0),
+        )?;
+        search::index_entry(&tx, rowid, &query_string, Some(&result))?;
+        if ns == ZH_NAMESPACE {
+            pinyin::index_entry(&tx, rowid, &query_string)?;
+        }
+        tx.commit()
+    })
+    .await?;
 
     Ok(())
 }
 
 async fn batch_insert_cache_impl(
     db: &Connection,
+    ns: &str,
     items: Vec<(String, QueryResult)>,
 ) -> Result<usize, KdError> {
-    use std::io::Cursor;
-    use tokio_rusqlite::params;
-    use zstd::stream::encode_all;
-
-    let now = chrono::Utc::now().timestamp();
-
     let prepared_items: Vec<_> = items
         .into_iter()
         .filter_map(|(query, result)| {
-            let serialized = serde_json::to_vec(&result).ok()?;
-            let compressed = encode_all(Cursor::new(&serialized), 0).ok()?;
-            let compressed_len = compressed.len();
-            let original_len = serialized.len();
-            Some((query, compressed, compressed_len, original_len))
+            let encoded = codec::encode(&result).ok()?;
+            Some((query, result, encoded))
         })
         .collect();
 
+    insert_encoded_batch_impl(db, ns, prepared_items).await
+}
+
+async fn insert_encoded_batch_impl(
+    db: &Connection,
+    ns: &str,
+    prepared_items: Vec<(String, QueryResult, EncodedEntry)>,
+) -> Result<usize, KdError> {
+    use tokio_rusqlite::params;
+
     if prepared_items.is_empty() {
         return Ok(0);
     }
 
-    let success_count = db.call(move |conn| {
-        let tx = conn.transaction()?;
-        let mut stmt = tx.prepare(
-            "INSERT OR REPLACE INTO cache (query, data, compressed_size, original_size, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?)"
-        )?;
+    let now = chrono::Utc::now().timestamp();
+    let ns = ns.to_string();
+    let success_count = db
+        .call(move |conn| {
+            let tx = conn.transaction()?;
+            let mut stmt = tx.prepare(UPSERT_CACHE)?;
 
-        let mut count = 0;
-        for (query, compressed, compressed_len, original_len) in prepared_items {
-            if stmt.execute(params![query, compressed, compressed_len, original_len, now, now]).is_ok() {
-                count += 1;
+            let mut count = 0;
+            for (query, result, encoded) in prepared_items {
+                let rowid = stmt.query_row(
+                    params![
+                        ns,
+                        query,
+                        encoded.data,
+                        encoded.compressed_size,
+                        encoded.original_size,
+                        now,
+                        now,
+                        row_source(&result)
+                    ],
+                    |row| row.get::<_, i64>(0),
+                );
+                if let Ok(rowid) = rowid {
+                    search::index_entry(&tx, rowid, &query, Some(&result))?;
+                    if ns == ZH_NAMESPACE {
+                        pinyin::index_entry(&tx, rowid, &query)?;
+                    }
+                    count += 1;
+                }
             }
-        }
 
-        stmt.finalize()?;
-        tx.commit()?;
-        Ok(count)
-    }).await?;
+            stmt.finalize()?;
+            tx.commit()?;
+            Ok(count)
+        })
+        .await?;
 
     Ok(success_count)
 }
@@ -0,0 +1,160 @@
+// Trained zstd dictionaries for cache blobs, kept in a side table
+use crate::domain::error::KdError;
+use crate::domain::model::DictionaryReport;
+use crate::infrastructure::storage::codec;
+use rusqlite::params;
+use tokio_rusqlite::Connection;
+
+/// Rows recompressed per statement while switching to a new dictionary
+const RECOMPRESS_BATCH: i64 = 1000;
+
+pub(crate) fn create_table(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS zstd_dicts (
+            version INTEGER PRIMARY KEY AUTOINCREMENT,
+            dict_id INTEGER NOT NULL UNIQUE,
+            data BLOB NOT NULL,
+            samples INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Register every stored dictionary with the codec, the latest as current
+pub(crate) fn load(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT data FROM zstd_dicts ORDER BY version")?;
+    let dictionaries = stmt
+        .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let latest = dictionaries.len().saturating_sub(1);
+    for (i, dictionary) in dictionaries.iter().enumerate() {
+        codec::register_dictionary(dictionary, i == latest);
+    }
+    Ok(())
+}
+
+/// Train a dictionary on up to `sample_count` random entries and recompress
+/// every entry with it
+///
+/// Rows that fail to decode are left as they are.
+pub async fn train(
+    db: &Connection,
+    sample_count: usize,
+    max_size: usize,
+) -> Result<DictionaryReport, KdError> {
+    let samples: Vec<Vec<u8>> = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT data FROM cache
+                 WHERE rowid IN (SELECT rowid FROM cache ORDER BY random() LIMIT ?)",
+            )?;
+            let rows =
+                stmt.query_map(params![sample_count as i64], |row| row.get::<_, Vec<u8>>(0))?;
+            rows.filter_map(|data| match data {
+                // Trained on the binary format, whatever the row holds now
+                Ok(data) => codec::decode(&data)
+                    .and_then(|result| codec::serialize(&result))
+                    .ok()
+                    .map(Ok),
+                Err(e) => Some(Err(e)),
+            })
+            .collect()
+        })
+        .await?;
+
+    let sampled = samples.len();
+    let dictionary =
+        tokio::task::spawn_blocking(move || zstd::dict::from_samples(&samples, max_size))
+            .await
+            .map_err(|e| KdError::Compression(e.to_string()))?
+            .map_err(|e| {
+                KdError::Compression(format!(
+                    "could not train a dictionary from {} entries: {}",
+                    sampled, e
+                ))
+            })?;
+    let dict_id = codec::dictionary_id(&dictionary)
+        .ok_or_else(|| KdError::Compression("trained dictionary has no ID".to_string()))?;
+
+    let stored = dictionary.clone();
+    let report = db
+        .call(move |conn| {
+            let encoder = codec::encoder_dictionary(&stored);
+            let tx = conn.transaction()?;
+            let compressed_before: i64 =
+                tx.query_row("SELECT COALESCE(SUM(compressed_size), 0) FROM cache", [], |row| {
+                    row.get(0)
+                })?;
+            // zstd derives the ID from the content: training on unchanged entries
+            // gives the same dictionary again, which then becomes the latest version
+            tx.execute("DELETE FROM zstd_dicts WHERE dict_id = ?", params![dict_id])?;
+            tx.execute(
+                "INSERT INTO zstd_dicts (dict_id, data, samples, created_at) VALUES (?, ?, ?, ?)",
+                params![
+                    dict_id,
+                    stored,
+                    sampled as i64,
+                    chrono::Utc::now().timestamp()
+                ],
+            )?;
+            let version = tx.last_insert_rowid();
+
+            let mut entries = 0;
+            let mut last_rowid = 0;
+            loop {
+                let batch: Vec<(i64, Vec<u8>)> = {
+                    let mut stmt = tx.prepare_cached(
+                        "SELECT rowid, data FROM cache WHERE rowid > ? ORDER BY rowid LIMIT ?",
+                    )?;
+                    let rows = stmt.query_map(params![last_rowid, RECOMPRESS_BATCH], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })?;
+                    rows.collect::<Result<_, _>>()?
+                };
+                let Some((rowid, _)) = batch.last() else {
+                    break;
+                };
+                last_rowid = *rowid;
+
+                let mut update = tx.prepare_cached(
+                    "UPDATE cache SET data = ?, compressed_size = ?, original_size = ? WHERE rowid = ?",
+                )?;
+                for (rowid, data) in batch {
+                    let Ok(encoded) = codec::recompress(&data, &encoder) else {
+                        continue;
+                    };
+                    update.execute(params![
+                        encoded.data,
+                        encoded.compressed_size,
+                        encoded.original_size,
+                        rowid
+                    ])?;
+                    entries += 1;
+                }
+            }
+
+            let (compressed_after, original_size): (i64, i64) = tx.query_row(
+                "SELECT COALESCE(SUM(compressed_size), 0), COALESCE(SUM(original_size), 0) FROM cache",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            tx.commit()?;
+
+            Ok(DictionaryReport {
+                version,
+                dict_size: stored.len(),
+                samples: sampled,
+                entries,
+                compressed_before,
+                compressed_after,
+                original_size,
+            })
+        })
+        .await?;
+
+    // Only once it's stored, so no row is written with a dictionary that could be lost
+    codec::register_dictionary(&dictionary, true);
+    Ok(report)
+}
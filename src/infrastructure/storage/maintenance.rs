@@ -0,0 +1,236 @@
+// Cache maintenance queries: listing, inspecting, deleting and compacting rows
+use crate::domain::error::KdError;
+use crate::domain::model::{CacheEntryInfo, CacheStats, QueryResult};
+use crate::infrastructure::storage::codec;
+use crate::infrastructure::storage::db::OFFLINE_SOURCE;
+use rusqlite::types::Value;
+use tokio_rusqlite::Connection;
+
+/// Which rows a listing or deletion applies to; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct CacheFilter {
+    pub ns: Option<String>,
+    pub source: Option<String>,
+    /// Only rows updated at or after this Unix timestamp
+    pub since: Option<i64>,
+    /// Only rows updated before this Unix timestamp
+    pub until: Option<i64>,
+}
+
+impl CacheFilter {
+    /// SQL conditions (joined with AND, never empty) and their parameters
+    fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut conditions = vec!["1 = 1".to_string()];
+        let mut params = Vec::new();
+        if let Some(ns) = &self.ns {
+            conditions.push("ns = ?".to_string());
+            params.push(Value::Text(ns.clone()));
+        }
+        if let Some(source) = &self.source {
+            conditions.push("source = ?".to_string());
+            params.push(Value::Text(source.clone()));
+        }
+        if let Some(since) = self.since {
+            conditions.push("updated_at >= ?".to_string());
+            params.push(Value::Integer(since));
+        }
+        if let Some(until) = self.until {
+            conditions.push("updated_at < ?".to_string());
+            params.push(Value::Integer(until));
+        }
+        (conditions.join(" AND "), params)
+    }
+}
+
+const ENTRY_COLUMNS: &str =
+    "ns, query, source, created_at, updated_at, compressed_size, original_size";
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<CacheEntryInfo> {
+    Ok(CacheEntryInfo {
+        ns: row.get(0)?,
+        query: row.get(1)?,
+        source: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        compressed_size: row.get(5)?,
+        original_size: row.get(6)?,
+    })
+}
+
+/// Whether `pattern` uses GLOB wildcards rather than naming one word
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Entries matching `filter`, most recently updated first
+pub async fn list_entries(
+    db: &Connection,
+    filter: &CacheFilter,
+    limit: usize,
+) -> Result<Vec<CacheEntryInfo>, KdError> {
+    let (conditions, mut params) = filter.to_sql();
+    params.push(Value::Integer(limit as i64));
+    let sql = format!(
+        "SELECT {} FROM cache WHERE {} ORDER BY updated_at DESC, query LIMIT ?",
+        ENTRY_COLUMNS, conditions
+    );
+
+    let entries = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), entry_from_row)?;
+            rows.collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
+
+    Ok(entries)
+}
+
+/// Every stored entry for `query` (one per namespace), decoded
+pub async fn find_entries(
+    db: &Connection,
+    query: &str,
+    ns: Option<&str>,
+) -> Result<Vec<(CacheEntryInfo, QueryResult)>, KdError> {
+    let filter = CacheFilter {
+        ns: ns.map(str::to_string),
+        ..CacheFilter::default()
+    };
+    let (conditions, mut params) = filter.to_sql();
+    params.push(Value::Text(query.to_string()));
+    let sql = format!(
+        "SELECT {}, data FROM cache WHERE {} AND query = ? ORDER BY ns",
+        ENTRY_COLUMNS, conditions
+    );
+
+    let entries = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+                let data: Vec<u8> = row.get(7)?;
+                let result = codec::decode(&data).map_err(codec::blob_error)?;
+                Ok((entry_from_row(row)?, result))
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
+
+    Ok(entries)
+}
+
+/// SQL conditions selecting rows whose headword equals `pattern` or matches it as a GLOB
+fn pattern_sql(pattern: &str, filter: &CacheFilter) -> (String, Vec<Value>) {
+    let (conditions, mut params) = filter.to_sql();
+    let operator = if is_glob(pattern) { "GLOB" } else { "=" };
+    params.push(Value::Text(pattern.to_string()));
+    (format!("{} AND query {} ?", conditions, operator), params)
+}
+
+/// Number of entries whose headword equals `pattern`, or matches it as a GLOB
+pub async fn count_entries(
+    db: &Connection,
+    pattern: &str,
+    filter: &CacheFilter,
+) -> Result<usize, KdError> {
+    let (conditions, params) = pattern_sql(pattern, filter);
+    let sql = format!("SELECT COUNT(*) FROM cache WHERE {}", conditions);
+
+    let count = db
+        .call(move |conn| {
+            conn.query_row(&sql, rusqlite::params_from_iter(params), |row| {
+                row.get::<_, i64>(0)
+            })
+        })
+        .await?;
+
+    Ok(count as usize)
+}
+
+/// Delete entries whose headword equals `pattern`, or matches it as a GLOB
+///
+/// Returns the number of rows deleted; the search and pinyin indexes follow
+/// through their delete triggers.
+pub async fn remove_entries(
+    db: &Connection,
+    pattern: &str,
+    filter: &CacheFilter,
+) -> Result<usize, KdError> {
+    let (conditions, params) = pattern_sql(pattern, filter);
+    let sql = format!("DELETE FROM cache WHERE {}", conditions);
+
+    let deleted = db
+        .call(move |conn| conn.execute(&sql, rusqlite::params_from_iter(params)))
+        .await?;
+
+    Ok(deleted)
+}
+
+/// Delete online results last written before `before` (a Unix timestamp)
+///
+/// Offline dictionary rows are never pruned. Returns the number of rows deleted.
+pub async fn prune_cache(db: &Connection, before: i64) -> Result<usize, KdError> {
+    let deleted = db
+        .call(move |conn| {
+            conn.execute(
+                "DELETE FROM cache WHERE updated_at < ? AND source != ?",
+                rusqlite::params![before, OFFLINE_SOURCE],
+            )
+        })
+        .await?;
+
+    Ok(deleted)
+}
+
+/// Compact the database file; returns its size in bytes before and after
+pub async fn vacuum(db: &Connection) -> Result<(u64, u64), KdError> {
+    let sizes = db
+        .call(|conn| {
+            let size = |conn: &rusqlite::Connection| -> rusqlite::Result<u64> {
+                conn.query_row(
+                    "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                    [],
+                    |row| row.get(0),
+                )
+            };
+            let before = size(conn)?;
+            conn.execute("INSERT INTO cache_fts (cache_fts) VALUES ('optimize')", [])?;
+            conn.execute("VACUUM", [])?;
+            Ok((before, size(conn)?))
+        })
+        .await?;
+
+    Ok(sizes)
+}
+
+/// Entry counts and stored sizes, overall and per source and namespace
+pub async fn cache_stats(db: &Connection) -> Result<CacheStats, KdError> {
+    let stats = db
+        .call(|conn| {
+            let (entries, compressed_size, original_size) = conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(compressed_size), 0), COALESCE(SUM(original_size), 0)
+                 FROM cache",
+                [],
+                |row| Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?)),
+            )?;
+            let counts = |column: &str| -> rusqlite::Result<_> {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {0}, COUNT(*) FROM cache GROUP BY {0}",
+                    column
+                ))?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+                })?;
+                rows.collect::<Result<_, _>>()
+            };
+            Ok(CacheStats {
+                entries: entries as usize,
+                compressed_size,
+                original_size,
+                by_source: counts("source")?,
+                by_namespace: counts("ns")?,
+            })
+        })
+        .await?;
+
+    Ok(stats)
+}
@@ -1,4 +1,9 @@
 // Storage implementations
 
 pub mod cache;
+pub mod codec;
 pub mod db;
+pub mod dictionary;
+pub mod maintenance;
+pub mod pinyin;
+pub mod search;
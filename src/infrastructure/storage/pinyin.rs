@@ -0,0 +1,119 @@
+// Pinyin index over Chinese headwords (rows in the "zh" namespace)
+use crate::domain::chinese::{pinyin_keys, PinyinKey};
+use crate::domain::error::KdError;
+use crate::infrastructure::storage::db::{get_meta, set_meta, ZH_NAMESPACE};
+use chrono::Utc;
+use rusqlite::params;
+use tokio_rusqlite::Connection;
+
+/// `meta` key recording that every Chinese headword has been indexed
+const INDEX_KEY: &str = "pinyin_index";
+
+/// Create the index table and the trigger that keeps it in step with deletions
+pub(crate) fn create_index(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS pinyin_index (
+            entry INTEGER NOT NULL,
+            plain TEXT NOT NULL,
+            tones TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_pinyin_plain ON pinyin_index(plain);
+        CREATE INDEX IF NOT EXISTS idx_pinyin_entry ON pinyin_index(entry);
+        CREATE TRIGGER IF NOT EXISTS pinyin_index_delete AFTER DELETE ON cache BEGIN
+            DELETE FROM pinyin_index WHERE entry = old.rowid;
+        END;",
+    )
+}
+
+/// (Re)index the readings of one Chinese headword; `rowid` is its row in `cache`
+pub(crate) fn index_entry(
+    conn: &rusqlite::Connection,
+    rowid: i64,
+    headword: &str,
+) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM pinyin_index WHERE entry = ?", params![rowid])?;
+
+    for key in pinyin_keys(headword) {
+        conn.execute(
+            "INSERT INTO pinyin_index (entry, plain, tones) VALUES (?, ?, ?)",
+            params![rowid, key.plain, key.tones],
+        )?;
+    }
+    Ok(())
+}
+
+/// Index Chinese headwords written before the index existed
+///
+/// Every write of a Chinese headword indexes it, so once built the index stays
+/// complete and `meta` says so. Returns the number of headwords indexed, 0 when
+/// the index was already complete.
+pub async fn ensure_pinyin_index(db: &Connection) -> Result<usize, KdError> {
+    if get_meta(db, INDEX_KEY).await?.is_some() {
+        return Ok(0);
+    }
+
+    let indexed = db
+        .call(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM pinyin_index", [])?;
+            let rows: Vec<(i64, String)> = {
+                let mut stmt = tx.prepare("SELECT rowid, query FROM cache WHERE ns = ?")?;
+                let rows =
+                    stmt.query_map(params![ZH_NAMESPACE], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                rows.collect::<Result<_, _>>()?
+            };
+            for (rowid, headword) in &rows {
+                index_entry(&tx, *rowid, headword)?;
+            }
+            tx.commit()?;
+            Ok(rows.len())
+        })
+        .await?;
+    set_meta(db, INDEX_KEY, &Utc::now().timestamp().to_string()).await?;
+
+    Ok(indexed)
+}
+
+/// Chinese headwords whose pinyin matches, best first
+///
+/// Readings whose tones match the input rank first, then shorter headwords.
+pub async fn lookup_pinyin(
+    db: &Connection,
+    key: &PinyinKey,
+    limit: usize,
+) -> Result<Vec<String>, KdError> {
+    let key = key.clone();
+    let headwords = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT c.query, p.tones, c.rowid FROM pinyin_index p
+                 JOIN cache c ON c.rowid = p.entry
+                 WHERE p.plain = ?",
+            )?;
+            let rows = stmt.query_map(params![key.plain], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })?;
+            let mut matches = rows.collect::<Result<Vec<_>, _>>()?;
+
+            matches.sort_by_key(|(headword, tones, rowid)| {
+                (*tones != key.tones, headword.chars().count(), *rowid)
+            });
+            let mut headwords: Vec<String> = Vec::new();
+            for (headword, _, _) in matches {
+                if !headwords.contains(&headword) {
+                    headwords.push(headword);
+                }
+                if headwords.len() >= limit {
+                    break;
+                }
+            }
+            Ok(headwords)
+        })
+        .await?;
+
+    Ok(headwords)
+}
@@ -0,0 +1,183 @@
+// Full-text search over cached entries (FTS5 index kept beside the cache table)
+use crate::domain::chinese::is_cjk;
+use crate::domain::error::KdError;
+use crate::domain::model::{QueryResult, SearchHit};
+use crate::infrastructure::storage::codec;
+use crate::infrastructure::storage::db::{get_meta, set_meta};
+use chrono::Utc;
+use rusqlite::params;
+use tokio_rusqlite::Connection;
+
+/// `meta` key recording that every cache row has been indexed
+const INDEX_KEY: &str = "search_index";
+
+/// Create the FTS table and the trigger that keeps it in step with deletions
+///
+/// Indexes from before summaries were built at display time carry a
+/// `summary` column; they are dropped and rebuilt on the next search.
+pub(crate) fn create_index(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    let outdated = conn
+        .prepare("SELECT 1 FROM pragma_table_info('cache_fts') WHERE name = 'summary'")?
+        .exists([])?;
+    if outdated {
+        conn.execute_batch("DROP TABLE cache_fts;")?;
+        conn.execute("DELETE FROM meta WHERE key = ?", params![INDEX_KEY])?;
+    }
+
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS cache_fts USING fts5(
+            query,
+            translations,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+        CREATE TRIGGER IF NOT EXISTS cache_fts_delete AFTER DELETE ON cache BEGIN
+            DELETE FROM cache_fts WHERE rowid = old.rowid;
+        END;",
+    )
+}
+
+/// (Re)index one cache row; `rowid` is the rowid of the row in `cache`
+pub(crate) fn index_entry(
+    conn: &rusqlite::Connection,
+    rowid: i64,
+    query: &str,
+    result: Option<&QueryResult>,
+) -> rusqlite::Result<()> {
+    let translations = result.map(summary).unwrap_or_default();
+
+    conn.execute("DELETE FROM cache_fts WHERE rowid = ?", params![rowid])?;
+    conn.execute(
+        "INSERT INTO cache_fts (rowid, query, translations) VALUES (?, ?, ?)",
+        params![rowid, query, segment_cjk(&translations)],
+    )?;
+    Ok(())
+}
+
+/// Build the index for rows written before it existed
+///
+/// Every write indexes its row, so once built the index stays complete and
+/// `meta` says so. Returns the number of entries indexed, 0 when the index
+/// was already complete.
+pub async fn ensure_search_index(db: &Connection) -> Result<usize, KdError> {
+    if get_meta(db, INDEX_KEY).await?.is_some() {
+        return Ok(0);
+    }
+
+    let indexed = db
+        .call(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM cache_fts", [])?;
+            let mut count = 0;
+            {
+                let mut stmt = tx.prepare("SELECT rowid, query, data FROM cache")?;
+                let mut rows = stmt.query([])?;
+                while let Some(row) = rows.next()? {
+                    let rowid: i64 = row.get(0)?;
+                    let query: String = row.get(1)?;
+                    let data: Vec<u8> = row.get(2)?;
+                    // Undecodable rows still get their headword indexed
+                    let result = codec::decode(&data).ok();
+                    index_entry(&tx, rowid, &query, result.as_ref())?;
+                    count += 1;
+                }
+            }
+            tx.commit()?;
+            Ok(count)
+        })
+        .await?;
+    set_meta(db, INDEX_KEY, &Utc::now().timestamp().to_string()).await?;
+
+    Ok(indexed)
+}
+
+/// Search headwords and translations, best matches first
+///
+/// - patterns containing `*` or `?` are matched as wildcards against headwords
+/// - patterns with Chinese characters search translations (reverse lookup)
+/// - anything else is a prefix search over headwords and translations
+pub async fn search_cache(
+    db: &Connection,
+    pattern: &str,
+    limit: usize,
+) -> Result<Vec<SearchHit>, KdError> {
+    let pattern = pattern.trim().to_string();
+    if pattern.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let hits = db
+        .call(move |conn| {
+            if pattern.contains(['*', '?']) {
+                let mut stmt = conn.prepare(
+                    "SELECT query, data FROM cache
+                     WHERE query GLOB ?
+                     ORDER BY length(query), query
+                     LIMIT ?",
+                )?;
+                let rows = stmt.query_map(params![pattern, limit as i64], hit_from_row)?;
+                return rows.collect::<Result<Vec<_>, _>>();
+            }
+
+            let expression = if pattern.chars().any(is_cjk) {
+                format!("translations : {}", fts_string(&segment_cjk(&pattern)))
+            } else {
+                pattern
+                    .split_whitespace()
+                    .map(|token| format!("{}*", fts_string(token)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+
+            // Headwords weigh more than translations; an exact headword match leads
+            let mut stmt = conn.prepare(
+                "SELECT c.query, c.data FROM cache_fts f
+                 JOIN cache c ON c.rowid = f.rowid
+                 WHERE cache_fts MATCH ?
+                 ORDER BY f.query = ? DESC, bm25(cache_fts, 10.0, 1.0), length(f.query)
+                 LIMIT ?",
+            )?;
+            let rows = stmt.query_map(params![expression, pattern, limit as i64], hit_from_row)?;
+            rows.collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
+
+    Ok(hits)
+}
+
+/// Summaries come from the stored result, so they can't drift from the entry
+fn hit_from_row(row: &rusqlite::Row) -> rusqlite::Result<SearchHit> {
+    let data: Vec<u8> = row.get(1)?;
+    Ok(SearchHit {
+        query: row.get(0)?,
+        summary: codec::decode(&data)
+            .map(|result| summary(&result))
+            .unwrap_or_default(),
+    })
+}
+
+fn summary(result: &QueryResult) -> String {
+    result.translations.join("; ")
+}
+
+/// Quote a term as an FTS5 string so operators in user input are literal
+fn fts_string(term: &str) -> String {
+    format!("\"{}\"", term.trim().replace('"', "\"\""))
+}
+
+/// Put spaces around CJK characters so each one is a token
+///
+/// unicode61 would otherwise treat a whole run of Chinese as one token, and
+/// searching 遗弃 inside "放弃；遗弃" could never match.
+fn segment_cjk(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    for c in text.chars() {
+        if is_cjk(c) {
+            out.push(' ');
+            out.push(c);
+            out.push(' ');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -1,3 +0,0 @@
-// API interfaces and abstractions
-
-pub mod translator;
@@ -0,0 +1,132 @@
+// Batch mode: read a word list, look it up concurrently and emit results
+use crate::application::batch::{lookup_all, parse_word_list, BatchItem, BatchSummary};
+use crate::domain::error::KdError;
+use crate::interfaces::cli::BatchFormat;
+use crate::presentation::theme::Theme;
+use crate::state::AppState;
+use colored::Colorize;
+use futures_util::StreamExt;
+use std::io::Write;
+use std::path::Path;
+
+pub struct BatchOptions {
+    pub format: BatchFormat,
+    pub jobs: usize,
+    pub no_cache: bool,
+    pub theme: String,
+    pub english_only: bool,
+    pub enable_emoji: bool,
+}
+
+/// Run a batch from `source` (a file path, or `-` for stdin)
+pub async fn run(state: &AppState, source: &Path, options: BatchOptions) -> Result<(), KdError> {
+    let input = if source == Path::new("-") {
+        tokio::task::spawn_blocking(|| std::io::read_to_string(std::io::stdin()))
+            .await
+            .map_err(|e| KdError::Io(std::io::Error::other(format!("Task join error: {}", e))))??
+    } else {
+        tokio::fs::read_to_string(source).await?
+    };
+    let words = parse_word_list(&input);
+
+    let theme = Theme::from_name(&options.theme);
+    let mut stdout = std::io::stdout();
+    let mut summary = BatchSummary::default();
+
+    if matches!(options.format, BatchFormat::Csv) {
+        writeln!(
+            stdout,
+            "query,found,source,pronunciation,translations,error"
+        )?;
+    }
+
+    let mut items = std::pin::pin!(lookup_all(state, words, options.jobs, options.no_cache));
+    while let Some(item) = items.next().await {
+        summary.record(&item);
+        match options.format {
+            BatchFormat::Pretty => write_pretty(&mut stdout, &item, &theme, &options)?,
+            BatchFormat::Jsonl => write_jsonl(&mut stdout, &item)?,
+            BatchFormat::Csv => write_csv(&mut stdout, &item)?,
+        }
+    }
+    stdout.flush()?;
+
+    // Summary goes to stderr so stdout stays machine-readable
+    eprintln!(
+        "{} {} words: {} found, {} not found, {} errors",
+        "Batch:".green().bold(),
+        summary.total,
+        summary.found,
+        summary.not_found,
+        summary.errors
+    );
+    if !summary.by_source.is_empty() {
+        let sources: Vec<String> = summary
+            .by_source
+            .iter()
+            .map(|(source, count)| format!("{} {}", source, count))
+            .collect();
+        eprintln!("{} {}", "Sources:".green().bold(), sources.join(", "));
+    }
+
+    Ok(())
+}
+
+fn write_pretty(
+    out: &mut impl Write,
+    item: &BatchItem,
+    theme: &Theme,
+    options: &BatchOptions,
+) -> Result<(), KdError> {
+    match &item.outcome {
+        Ok(result) => write!(
+            out,
+            "{}",
+            crate::format_result(result, theme, options.english_only, options.enable_emoji)
+        )?,
+        Err(e) => writeln!(out, "{}\n", format!("✘ {}: {}", item.query, e).red())?,
+    }
+    Ok(())
+}
+
+fn write_jsonl(out: &mut impl Write, item: &BatchItem) -> Result<(), KdError> {
+    let line = match &item.outcome {
+        Ok(result) => serde_json::to_string(result)?,
+        Err(e) => serde_json::json!({ "query": item.query, "error": e }).to_string(),
+    };
+    writeln!(out, "{}", line)?;
+    Ok(())
+}
+
+fn write_csv(out: &mut impl Write, item: &BatchItem) -> Result<(), KdError> {
+    let fields = match &item.outcome {
+        Ok(result) => [
+            item.query.clone(),
+            result.found.to_string(),
+            result.source.name().to_string(),
+            result.pronunciation.clone().unwrap_or_default(),
+            result.translations.join(" | "),
+            String::new(),
+        ],
+        Err(e) => [
+            item.query.clone(),
+            "false".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            e.clone(),
+        ],
+    };
+    let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+    writeln!(out, "{}", line.join(","))?;
+    Ok(())
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
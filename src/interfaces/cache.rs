@@ -0,0 +1,221 @@
+// `kd cache` subcommands: maintenance of the local result cache
+use crate::application::cache;
+use crate::domain::error::KdError;
+use crate::domain::model::{CacheEntryInfo, CacheStats, MemoryCacheStats, QueryResult};
+use crate::interfaces::cli::CacheCommand;
+use crate::state::AppState;
+use colored::Colorize;
+use serde::Serialize;
+
+/// A stored entry as printed by `kd cache show`: its row metadata plus the decoded result
+#[derive(Serialize)]
+struct ShownEntry<'a> {
+    #[serde(flatten)]
+    info: &'a CacheEntryInfo,
+    data: &'a QueryResult,
+}
+
+pub async fn run(state: &AppState, command: &CacheCommand) -> Result<(), KdError> {
+    match command {
+        CacheCommand::Ls {
+            filter,
+            limit,
+            json,
+        } => {
+            let entries = cache::list(state, &filter.into(), *limit).await?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else {
+                print!("{}", format_entries(&entries));
+            }
+        }
+        CacheCommand::Show { word, ns } => {
+            let entries = cache::show(state, word, ns.as_deref()).await?;
+            if entries.is_empty() {
+                println!("{}", format!("No cached entry for \"{}\"", word).red());
+                return Ok(());
+            }
+            let shown: Vec<ShownEntry> = entries
+                .iter()
+                .map(|(info, data)| ShownEntry { info, data })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&shown)?);
+        }
+        CacheCommand::Rm {
+            pattern,
+            filter,
+            yes,
+        } => {
+            let removed = cache::remove(state, pattern, &filter.into(), *yes).await?;
+            println!("{}", format!("Removed {} cached entries", removed).green());
+        }
+        CacheCommand::Vacuum => {
+            let (before, after) = cache::vacuum(state).await?;
+            println!(
+                "{}",
+                format!(
+                    "Database compacted: {} → {}",
+                    format_bytes(before as i64),
+                    format_bytes(after as i64)
+                )
+                .green()
+            );
+        }
+        CacheCommand::Stats { json } => {
+            let stats = cache::stats(state).await?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                print!("{}", format_stats(&stats));
+            }
+        }
+        CacheCommand::TrainDict { samples, size } => {
+            let report = cache::train_dictionary(state, *samples, *size).await?;
+            println!(
+                "{}",
+                format!(
+                    "Trained dictionary v{} ({}) from {} entries",
+                    report.version,
+                    format_bytes(report.dict_size as i64),
+                    report.samples
+                )
+                .green()
+            );
+            println!(
+                "Recompressed {} entries: {} → {} (original {}, ratio {} → {})",
+                report.entries,
+                format_bytes(report.compressed_before),
+                format_bytes(report.compressed_after),
+                format_bytes(report.original_size),
+                format_ratio(report.original_size, report.compressed_before),
+                format_ratio(report.original_size, report.compressed_after)
+            );
+            println!("Run `kd cache vacuum` to return the freed space to the file system");
+        }
+        CacheCommand::Prune { older_than } => {
+            let pruned = cache::prune(state, *older_than).await?;
+            println!(
+                "{}",
+                format!("Pruned {} cached online results", pruned).green()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// One line per entry: update time, source, namespace, headword and stored size
+fn format_entries(entries: &[CacheEntryInfo]) -> String {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    if entries.is_empty() {
+        writeln!(output, "{}", "No cached entries".red()).ok();
+        return output;
+    }
+
+    let source_width = entries.iter().map(|e| e.source.len()).max().unwrap_or(0);
+    let ns_width = entries.iter().map(|e| e.ns.len()).max().unwrap_or(0);
+    for entry in entries {
+        writeln!(
+            output,
+            "{}  {:<source_width$}  {:<ns_width$}  {}  {}",
+            format_time(entry.updated_at).dimmed(),
+            entry.source,
+            entry.ns,
+            entry.query.bold(),
+            format_bytes(entry.compressed_size).dimmed(),
+        )
+        .ok();
+    }
+    output
+}
+
+fn format_stats(stats: &CacheStats) -> String {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    writeln!(output, "{}", "kd Cache".green().bold()).ok();
+    writeln!(output, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━").ok();
+    writeln!(output, "Entries: {}", stats.entries).ok();
+    writeln!(
+        output,
+        "Original size: {}",
+        format_bytes(stats.original_size)
+    )
+    .ok();
+    writeln!(
+        output,
+        "Compressed size: {}",
+        format_bytes(stats.compressed_size)
+    )
+    .ok();
+    writeln!(
+        output,
+        "Compression ratio: {}",
+        format_ratio(stats.original_size, stats.compressed_size)
+    )
+    .ok();
+
+    for (label, counts) in [
+        ("By source", &stats.by_source),
+        ("By namespace", &stats.by_namespace),
+    ] {
+        writeln!(output, "{}:", label).ok();
+        let width = counts.keys().map(String::len).max().unwrap_or(0);
+        for (name, count) in counts {
+            writeln!(output, "  {:<width$}  {}", name, count).ok();
+        }
+    }
+    output
+}
+
+/// One-line summary of the in-memory cache for `kd --status`
+pub fn format_memory_cache(stats: &MemoryCacheStats) -> String {
+    let limit = match stats.max_bytes {
+        0 => "no limit".to_string(),
+        max => format_bytes(max as i64),
+    };
+    format!(
+        "{} entries, {} / {}, {} hits, {} misses, {} evictions",
+        stats.entries,
+        format_bytes(stats.bytes as i64),
+        limit,
+        stats.hits,
+        stats.misses,
+        stats.evictions
+    )
+}
+
+/// Local date and time of a Unix timestamp; "-" for rows that never recorded one
+fn format_time(timestamp: i64) -> String {
+    match chrono::DateTime::from_timestamp(timestamp, 0) {
+        Some(time) if timestamp > 0 => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        _ => format!("{:<16}", "-"),
+    }
+}
+
+fn format_ratio(original: i64, compressed: i64) -> String {
+    if compressed > 0 {
+        format!("{:.2}x", original as f64 / compressed as f64)
+    } else {
+        "-".to_string()
+    }
+}
+
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -1,10 +1,20 @@
-use clap::Parser;
+use crate::domain::error::KdError;
+use crate::infrastructure::config::{parse_duration, parse_size};
+use crate::infrastructure::storage::maintenance::CacheFilter;
+use chrono::{Local, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "kd")]
 #[command(about = "A crystal clear command-line dictionary.")]
 #[command(version)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Translate long query
     #[arg(short = 't', long)]
     pub text: bool,
@@ -17,6 +27,14 @@ pub struct Cli {
     #[arg(long)]
     pub json: bool,
 
+    /// Source language, e.g. en, ja, de ("auto" detects it, "pinyin" reads pinyin)
+    #[arg(long, value_name = "LANG")]
+    pub from: Option<String>,
+
+    /// Target language, e.g. zh, en, ja ("auto": Chinese → English, others → Chinese)
+    #[arg(long, value_name = "LANG")]
+    pub to: Option<String>,
+
     /// Choose color theme
     #[arg(short = 'T', long)]
     pub theme: Option<String>,
@@ -37,7 +55,165 @@ pub struct Cli {
     #[arg(long)]
     pub status: bool,
 
+    /// Start an interactive lookup session
+    #[arg(short = 'i', long)]
+    pub interactive: bool,
+
+    /// Look up every word in a file, one per line ("-" reads stdin)
+    #[arg(long, value_name = "FILE")]
+    pub batch: Option<PathBuf>,
+
+    /// Output format for batch mode [default: pretty, or jsonl with --json]
+    #[arg(long, value_enum, requires = "batch")]
+    pub format: Option<BatchFormat>,
+
+    /// Maximum concurrent lookups in batch mode
+    #[arg(short = 'j', long, default_value_t = 8, requires = "batch")]
+    pub jobs: usize,
+
+    /// Search headwords and meanings: prefix, wildcard (* ?) or Chinese meaning
+    #[arg(short = 's', long, value_name = "PATTERN")]
+    pub search: Option<String>,
+
+    /// Maximum number of search results
+    #[arg(long, default_value_t = 20, requires = "search")]
+    pub limit: usize,
+
+    /// Run a foreground server answering queries over a Unix socket
+    #[arg(long)]
+    pub daemon: bool,
+
     /// Query text
     #[arg(num_args = 1..)]
     pub query: Vec<String>,
 }
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum BatchFormat {
+    /// Formatted like a normal lookup
+    Pretty,
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run a local HTTP JSON server
+    Serve {
+        /// Address to listen on, e.g. 127.0.0.1:8765 (defaults to config)
+        #[arg(long)]
+        listen: Option<String>,
+    },
+    /// Update the offline dictionary (same as --update-dict)
+    UpdateDict {
+        /// Install from a downloaded kd_data.zip, a legacy .db file or a directory
+        /// holding one, without network access
+        #[arg(long, value_name = "PATH")]
+        from: Option<PathBuf>,
+    },
+    /// Manage the local result cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// List cached entries, most recently updated first
+    Ls {
+        #[command(flatten)]
+        filter: CacheFilterArgs,
+        /// Maximum number of entries to list
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the stored entries for a word as decoded JSON
+    Show {
+        word: String,
+        /// Only this namespace: en, zh or a language pair such as en-ja
+        #[arg(long)]
+        ns: Option<String>,
+    },
+    /// Delete entries by word or glob pattern (* ? [...])
+    Rm {
+        pattern: String,
+        #[command(flatten)]
+        filter: CacheFilterArgs,
+        /// Also delete matching offline dictionary entries
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Compact the database file
+    Vacuum,
+    /// Show stored sizes, compression ratio and entry counts per source
+    Stats {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Train a zstd dictionary on stored entries and recompress the cache with it
+    TrainDict {
+        /// Number of random entries to train on
+        #[arg(long, default_value_t = 10_000)]
+        samples: usize,
+        /// Maximum dictionary size, such as 110KB
+        #[arg(long, default_value = "110KB", value_parser = parse_size)]
+        size: usize,
+    },
+    /// Delete cached online results older than a duration (offline entries are kept)
+    Prune {
+        /// Age such as 30d, 12h or 1w
+        #[arg(long, value_parser = parse_duration)]
+        older_than: Duration,
+    },
+}
+
+/// Filters shared by `kd cache ls` and `kd cache rm`
+#[derive(Args)]
+pub struct CacheFilterArgs {
+    /// Only entries from this source: offline, youdao, bing, google, libretranslate or a template name
+    #[arg(long)]
+    pub source: Option<String>,
+
+    /// Only this namespace: en, zh or a language pair such as en-ja
+    #[arg(long)]
+    pub ns: Option<String>,
+
+    /// Only entries updated since a date (2024-05-01) or an age (7d)
+    #[arg(long, value_name = "WHEN", value_parser = parse_when)]
+    pub since: Option<i64>,
+
+    /// Only entries updated before a date (2024-05-01) or an age (30d)
+    #[arg(long, value_name = "WHEN", value_parser = parse_when)]
+    pub until: Option<i64>,
+}
+
+impl From<&CacheFilterArgs> for CacheFilter {
+    fn from(args: &CacheFilterArgs) -> Self {
+        Self {
+            ns: args.ns.clone(),
+            source: args.source.clone(),
+            since: args.since,
+            until: args.until,
+        }
+    }
+}
+
+/// Parse a point in time as a Unix timestamp: a local date or an age ago
+fn parse_when(text: &str) -> Result<i64, KdError> {
+    if let Ok(date) = NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .map(|time| time.timestamp())
+            .ok_or_else(|| KdError::Config(format!("invalid date {:?}", text)));
+    }
+    let age = parse_duration(text)?;
+    Ok(Utc::now().timestamp() - age.as_secs() as i64)
+}
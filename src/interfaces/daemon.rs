@@ -0,0 +1,187 @@
+// Daemon mode: keeps AppState alive and serves queries over a Unix domain socket
+//
+// The daemon runs in the foreground; put it in the background with the shell
+// (`kd --daemon &`) or a service manager.
+//
+// The protocol is line-delimited JSON: each request line gets exactly one
+// response line, and a connection may carry any number of requests.
+use crate::application::query::query_word;
+use crate::domain::error::KdError;
+use crate::domain::model::{MemoryCacheStats, QueryResult};
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+/// How long a client waits for the daemon to answer before giving up
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum DaemonRequest {
+    Query {
+        query: String,
+        #[serde(default)]
+        no_cache: bool,
+        #[serde(default)]
+        is_long_text: bool,
+    },
+    Status,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DaemonResponse {
+    Result { result: Box<QueryResult> },
+    Status { pid: u32, cache: MemoryCacheStats },
+    Error { message: String },
+}
+
+/// Run the daemon until `shutdown` fires
+pub async fn run(
+    state: AppState,
+    socket_path: &Path,
+    shutdown: tokio::sync::oneshot::Receiver<()>,
+) -> Result<(), KdError> {
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).await.is_ok() {
+            return Err(KdError::Init(format!(
+                "Daemon already running at {}",
+                socket_path.display()
+            )));
+        }
+        // Left over from a daemon that didn't shut down cleanly
+        tokio::fs::remove_file(socket_path).await?;
+    }
+
+    let listener = bind_private(socket_path)?;
+    println!(
+        "kd daemon listening on {} (in the foreground, Ctrl-C to stop)",
+        socket_path.display()
+    );
+
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                match accepted {
+                    Ok((stream, _)) => {
+                        let state = state.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_connection(&state, stream).await {
+                                tracing::warn!("Daemon connection error: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => tracing::warn!("Daemon accept error: {}", e),
+                }
+            }
+            _ = &mut shutdown => break,
+        }
+    }
+
+    tokio::fs::remove_file(socket_path).await.ok();
+    println!("kd daemon stopped");
+    Ok(())
+}
+
+/// Bind the socket so that only the owner may ever talk to the daemon
+///
+/// The socket is created inside a private (0700) directory and restricted to
+/// 0600 there, then moved into place, so other users never see it open.
+fn bind_private(socket_path: &Path) -> Result<UnixListener, KdError> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let mut staging = socket_path.as_os_str().to_owned();
+    staging.push(".new");
+    let staging = PathBuf::from(staging);
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+
+    let staged_socket = staging.join("kd.sock");
+    let bound = UnixListener::bind(&staged_socket)
+        .map_err(KdError::from)
+        .and_then(|listener| {
+            std::fs::set_permissions(&staged_socket, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&staged_socket, socket_path)?;
+            Ok(listener)
+        });
+    std::fs::remove_dir_all(&staging).ok();
+    bound
+}
+
+async fn handle_connection(state: &AppState, stream: UnixStream) -> Result<(), KdError> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<DaemonRequest>(&line) {
+            Ok(request) => dispatch(state, request).await,
+            Err(e) => DaemonResponse::Error {
+                message: format!("Invalid request: {}", e),
+            },
+        };
+        let mut payload = serde_json::to_vec(&response)?;
+        payload.push(b'\n');
+        writer.write_all(&payload).await?;
+    }
+
+    Ok(())
+}
+
+async fn dispatch(state: &AppState, request: DaemonRequest) -> DaemonResponse {
+    match request {
+        DaemonRequest::Query {
+            query,
+            no_cache,
+            is_long_text,
+        } => match query_word(state, &query, no_cache, is_long_text).await {
+            Ok(result) => DaemonResponse::Result {
+                result: Box::new(result),
+            },
+            Err(e) => DaemonResponse::Error {
+                message: e.to_string(),
+            },
+        },
+        DaemonRequest::Status => DaemonResponse::Status {
+            pid: std::process::id(),
+            cache: state.cache.stats(),
+        },
+    }
+}
+
+/// Send a request to a running daemon
+///
+/// Returns `None` when no daemon is listening, so callers can fall back to
+/// handling the request in-process.
+pub async fn send(
+    socket_path: &Path,
+    request: &DaemonRequest,
+) -> Option<Result<DaemonResponse, KdError>> {
+    let stream = UnixStream::connect(socket_path).await.ok()?;
+    Some(exchange(stream, request).await)
+}
+
+async fn exchange(stream: UnixStream, request: &DaemonRequest) -> Result<DaemonResponse, KdError> {
+    let (reader, mut writer) = stream.into_split();
+    let mut payload = serde_json::to_vec(request)?;
+    payload.push(b'\n');
+    writer.write_all(&payload).await?;
+
+    let mut line = String::new();
+    tokio::time::timeout(
+        RESPONSE_TIMEOUT,
+        BufReader::new(reader).read_line(&mut line),
+    )
+    .await
+    .map_err(|_| KdError::Api("Timed out waiting for daemon".to_string()))??;
+
+    Ok(serde_json::from_str(&line)?)
+}
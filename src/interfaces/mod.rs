@@ -1,4 +1,9 @@
 // Interfaces layer: External interfaces (CLI, API, etc.)
 
-pub mod api;
+pub mod batch;
+pub mod cache;
 pub mod cli;
+#[cfg(unix)]
+pub mod daemon;
+pub mod repl;
+pub mod server;
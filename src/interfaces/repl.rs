@@ -0,0 +1,161 @@
+// Interactive REPL: one AppState serves every lookup of the session
+use crate::application::query::query_word;
+use crate::domain::error::KdError;
+use crate::presentation::theme::Theme;
+use crate::state::AppState;
+use colored::Colorize;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::path::Path;
+
+const HELP: &str = "\
+Type a word, phrase or pinyin to look it up, or the number of a
+suggestion listed under the previous result. Commands:
+  :theme [name]   Show or switch the color theme (temp/wudao/canvas)
+  :nocache        Toggle cache bypass
+  :json           Toggle JSON output
+  :en             Toggle English-only output
+  :help           Show this help
+  :q              Quit (Ctrl-D also works)";
+
+/// Session settings that slash-commands can toggle
+pub struct ReplSettings {
+    pub theme: String,
+    pub no_cache: bool,
+    pub json: bool,
+    pub english_only: bool,
+    pub enable_emoji: bool,
+}
+
+enum Flow {
+    Continue,
+    Quit,
+}
+
+/// Run the prompt loop until the user quits or `shutdown` fires
+pub async fn run(
+    state: &AppState,
+    mut settings: ReplSettings,
+    history_path: &Path,
+    shutdown: tokio::sync::oneshot::Receiver<()>,
+) -> Result<(), KdError> {
+    let mut editor = DefaultEditor::new()
+        .map_err(|e| KdError::Init(format!("Failed to start line editor: {}", e)))?;
+    // A missing history file is normal on first run
+    editor.load_history(history_path).ok();
+
+    let mut theme = Theme::from_name(&settings.theme);
+    let mut suggestions: Vec<String> = Vec::new();
+    tokio::pin!(shutdown);
+
+    println!("kd interactive mode, type :help for commands");
+    loop {
+        let line = match tokio::task::block_in_place(|| editor.readline("kd> ")) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(KdError::Io(std::io::Error::other(e))),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line).ok();
+
+        if let Some(command) = line.strip_prefix(':') {
+            match handle_command(command, &mut settings, &mut theme) {
+                Flow::Continue => continue,
+                Flow::Quit => break,
+            }
+        }
+
+        // A bare number picks one of the suggestions listed under the previous result
+        let query = match line.parse::<usize>() {
+            Ok(n) if (1..=suggestions.len()).contains(&n) => suggestions[n - 1].clone(),
+            _ => line.to_string(),
+        };
+
+        let result = tokio::select! {
+            result = query_word(state, &query, settings.no_cache, false) => result,
+            _ = &mut shutdown => break,
+        };
+        suggestions = match &result {
+            Ok(result) => result.suggestions.clone(),
+            Err(_) => Vec::new(),
+        };
+        match result {
+            Ok(result) if settings.json => match serde_json::to_string_pretty(&result) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("{}", e.to_string().red()),
+            },
+            Ok(result) => print!(
+                "{}",
+                crate::format_result(
+                    &result,
+                    &theme,
+                    settings.english_only,
+                    settings.enable_emoji
+                )
+            ),
+            Err(e) => eprintln!("{}", e.to_string().red()),
+        }
+    }
+
+    if let Some(parent) = history_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if let Err(e) = editor.save_history(history_path) {
+        tracing::warn!("Failed to save REPL history: {}", e);
+    }
+
+    Ok(())
+}
+
+fn handle_command(command: &str, settings: &mut ReplSettings, theme: &mut Theme) -> Flow {
+    let mut parts = command.split_whitespace();
+    let name = parts.next().unwrap_or("");
+    let on_off = |v: bool| if v { "on" } else { "off" };
+
+    match name {
+        "q" | "quit" | "exit" => return Flow::Quit,
+        "help" | "h" | "?" => println!("{}", HELP),
+        "theme" => match parts.next() {
+            Some(name) => match Theme::named(name) {
+                Some(named) => {
+                    *theme = named;
+                    settings.theme = name.to_string();
+                    println!("theme: {}", name);
+                }
+                // Keep the current theme rather than falling back to the default
+                None => eprintln!(
+                    "{}",
+                    format!(
+                        "Unknown theme {}, available: {}",
+                        name,
+                        Theme::NAMES.join(", ")
+                    )
+                    .red()
+                ),
+            },
+            None => println!("theme: {}", settings.theme),
+        },
+        "nocache" => {
+            settings.no_cache = !settings.no_cache;
+            println!("nocache: {}", on_off(settings.no_cache));
+        }
+        "json" => {
+            settings.json = !settings.json;
+            println!("json: {}", on_off(settings.json));
+        }
+        "en" => {
+            settings.english_only = !settings.english_only;
+            println!("english_only: {}", on_off(settings.english_only));
+        }
+        _ => eprintln!(
+            "{}",
+            format!("Unknown command :{}, type :help for commands", name).red()
+        ),
+    }
+
+    Flow::Continue
+}
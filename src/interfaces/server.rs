@@ -0,0 +1,185 @@
+// Local HTTP JSON server exposing query_word to browser extensions and tools
+use crate::application::query::query_word;
+use crate::domain::error::KdError;
+use crate::domain::model::HistoryEntry;
+use crate::infrastructure::config::ServerConfig;
+use crate::infrastructure::network::provider::ProviderChain;
+use crate::infrastructure::storage::db::{count_cache, insert_history, recent_history};
+use crate::state::AppState;
+use axum::extract::{Query, Request, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+#[derive(Deserialize)]
+struct QueryParams {
+    q: Option<String>,
+    #[serde(default)]
+    nocache: bool,
+    #[serde(default)]
+    text: bool,
+}
+
+#[derive(Deserialize)]
+struct HistoryParams {
+    limit: Option<usize>,
+}
+
+/// Error returned as `{"error": "..."}` with a matching status code
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<KdError> for ApiError {
+    fn from(e: KdError) -> Self {
+        let status = match e {
+            KdError::Http(_) | KdError::Api(_) => StatusCode::BAD_GATEWAY,
+            KdError::Config(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, e.to_string())
+    }
+}
+
+/// Serve until `shutdown` fires
+pub async fn run(
+    state: AppState,
+    config: &ServerConfig,
+    listen: &str,
+    shutdown: tokio::sync::oneshot::Receiver<()>,
+) -> Result<(), KdError> {
+    let app = router(state, config)?;
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    println!("kd server listening on http://{}", listener.local_addr()?);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            shutdown.await.ok();
+        })
+        .await?;
+
+    Ok(())
+}
+
+fn router(state: AppState, config: &ServerConfig) -> Result<Router, KdError> {
+    let mut app = Router::new()
+        .route("/query", get(query_handler))
+        .route("/status", get(status_handler))
+        .route("/history", get(history_handler))
+        .with_state(state);
+
+    if let Some(token) = config.token.as_deref().filter(|t| !t.is_empty()) {
+        let expected = format!("Bearer {}", token);
+        app = app.layer(middleware::from_fn(move |request: Request, next: Next| {
+            let authorized = request
+                .headers()
+                .get(header::AUTHORIZATION)
+                .is_some_and(|value| value.as_bytes() == expected.as_bytes());
+            async move {
+                if authorized {
+                    next.run(request).await
+                } else {
+                    ApiError(StatusCode::UNAUTHORIZED, "Unauthorized".to_string()).into_response()
+                }
+            }
+        }));
+    }
+
+    // CORS goes outermost so preflight requests don't need the token
+    if !config.cors_origins.is_empty() {
+        app = app.layer(cors_layer(&config.cors_origins)?);
+    }
+
+    Ok(app)
+}
+
+fn cors_layer(origins: &[String]) -> Result<CorsLayer, KdError> {
+    let allow_origin = if origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|o| {
+                HeaderValue::from_str(o)
+                    .map_err(|_| KdError::Config(format!("Invalid CORS origin: {}", o)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET])
+        .allow_headers([header::AUTHORIZATION]))
+}
+
+async fn query_handler(
+    State(state): State<AppState>,
+    Query(params): Query<QueryParams>,
+) -> Result<Response, ApiError> {
+    let query = params
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .ok_or_else(|| {
+            ApiError(
+                StatusCode::BAD_REQUEST,
+                "Missing query parameter q".to_string(),
+            )
+        })?;
+
+    let result = query_word(&state, query, params.nocache, params.text).await?;
+
+    // History is best effort: a failed write shouldn't fail the lookup
+    let limit = state.config.read().await.server.history_limit;
+    if limit > 0 {
+        let entry = HistoryEntry {
+            query: query.to_string(),
+            found: result.found,
+            source: result.source.name().to_string(),
+            queried_at: Utc::now().timestamp(),
+        };
+        if let Err(e) = insert_history(&state.db, entry, limit).await {
+            tracing::warn!("Failed to record query history: {}", e);
+        }
+    }
+
+    Ok(Json(result).into_response())
+}
+
+async fn status_handler(State(state): State<AppState>) -> Result<Response, ApiError> {
+    let records = count_cache(&state.db).await?;
+    let providers = {
+        let config = state.config.read().await;
+        ProviderChain::from_config(&state.http_client, &config).names()
+    };
+
+    Ok(Json(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "database_records": records,
+        "memory_cache_entries": state.cache.len(),
+        "memory_cache": state.cache.stats(),
+        "providers": providers,
+    }))
+    .into_response())
+}
+
+async fn history_handler(
+    State(state): State<AppState>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Response, ApiError> {
+    let limit = params.limit.unwrap_or(50).min(1000);
+    let entries = recent_history(&state.db, limit).await?;
+    Ok(Json(entries).into_response())
+}
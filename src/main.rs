@@ -10,7 +10,7 @@ mod state;
 use clap::Parser;
 use colored::Colorize;
 use infrastructure::config::load_config;
-use interfaces::cli::Cli;
+use interfaces::cli::{Cli, Command};
 use state::AppState;
 
 #[tokio::main]
@@ -29,13 +29,40 @@ async fn main() -> anyhow::Result<()> {
     });
 
     let cli = Cli::parse();
-    let config = load_config()?;
+    let mut config = load_config()?;
+    // --from/--to override the configured default languages
+    if let Some(from) = &cli.from {
+        config.source_lang = from.clone();
+    }
+    if let Some(to) = &cli.to {
+        config.target_lang = to.clone();
+    }
 
     // Initialize logging
     if config.logging.enable {
         init_logging(&config.logging)?;
     }
 
+    // Forward plain queries to a running daemon, skipping local start-up entirely
+    #[cfg(unix)]
+    if !cli.daemon && is_plain_query(&cli) {
+        use interfaces::daemon::{DaemonRequest, DaemonResponse};
+
+        let socket_path = infrastructure::config::get_socket_path(&config);
+        let request = DaemonRequest::Query {
+            query: cli.query.join(" "),
+            no_cache: cli.nocache,
+            is_long_text: cli.text,
+        };
+        if let Some(response) = interfaces::daemon::send(&socket_path, &request).await {
+            match response? {
+                DaemonResponse::Result { result } => return print_result(&cli, &config, &result),
+                DaemonResponse::Error { message } => anyhow::bail!(message),
+                DaemonResponse::Status { .. } => anyhow::bail!("Unexpected daemon response"),
+            }
+        }
+    }
+
     // Setup database path (from config or default)
     let db_path = infrastructure::config::get_database_path(&config);
     if let Some(parent) = db_path.parent() {
@@ -44,13 +71,27 @@ async fn main() -> anyhow::Result<()> {
 
     // Initialize AppState
     let db_conn = infrastructure::storage::db::init_database(&db_path).await?;
-    let state = AppState::new(db_conn, config.clone())?;
+    migration::keys::normalize_cache_keys(&db_conn, &config.normalization).await?;
+    let mut state = AppState::new(db_conn, config.clone())?;
+    // Modes that look up many words keep a spelling index in memory
+    let long_running = cli.interactive
+        || cli.daemon
+        || cli.batch.is_some()
+        || matches!(cli.command, Some(Command::Serve { .. }));
+    if long_running {
+        state = state.with_suggestion_index();
+    }
 
     // Handle commands (flags)
-    if cli.update_dict {
+    let update_from = match &cli.command {
+        Some(Command::UpdateDict { from }) => Some(from.as_deref()),
+        _ if cli.update_dict => Some(None),
+        _ => None,
+    };
+    if let Some(from) = update_from {
         // Use select! to handle shutdown during update
         tokio::select! {
-            result = application::update::update_dict(&state) => {
+            result = application::update::update_dict(&state, from) => {
                 result?;
             }
             _ = shutdown_rx => {
@@ -84,6 +125,68 @@ async fn main() -> anyhow::Result<()> {
         print_status(&state).await?;
         return Ok(());
     }
+    if let Some(Command::Cache { command }) = &cli.command {
+        interfaces::cache::run(&state, command).await?;
+        return Ok(());
+    }
+    if let Some(Command::Serve { listen }) = &cli.command {
+        let listen = listen.as_deref().unwrap_or(config.server.listen.as_str());
+        interfaces::server::run(state, &config.server, listen, shutdown_rx).await?;
+        return Ok(());
+    }
+    if let Some(source) = &cli.batch {
+        // --json selects JSON Lines unless a format was given explicitly
+        let format = cli.format.unwrap_or(if cli.json {
+            interfaces::cli::BatchFormat::Jsonl
+        } else {
+            interfaces::cli::BatchFormat::Pretty
+        });
+        let options = interfaces::batch::BatchOptions {
+            format,
+            jobs: cli.jobs,
+            no_cache: cli.nocache,
+            theme: cli.theme.clone().unwrap_or_else(|| config.theme.clone()),
+            english_only: config.english_only,
+            enable_emoji: config.enable_emoji,
+        };
+        interfaces::batch::run(&state, source, options).await?;
+        state.drain_refreshes().await;
+        return Ok(());
+    }
+    if let Some(pattern) = &cli.search {
+        let hits = application::search::search_entries(&state, pattern, cli.limit).await?;
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&hits)?);
+        } else {
+            let theme_name = cli.theme.as_deref().unwrap_or(config.theme.as_str());
+            let theme = presentation::theme::Theme::from_name(theme_name);
+            print!("{}", format_search_hits(pattern, &hits, &theme));
+        }
+        return Ok(());
+    }
+    if cli.interactive {
+        let settings = interfaces::repl::ReplSettings {
+            theme: cli.theme.clone().unwrap_or_else(|| config.theme.clone()),
+            no_cache: cli.nocache,
+            json: cli.json,
+            english_only: config.english_only,
+            enable_emoji: config.enable_emoji,
+        };
+        let history_path = infrastructure::config::get_repl_history_path(&config);
+        interfaces::repl::run(&state, settings, &history_path, shutdown_rx).await?;
+        state.drain_refreshes().await;
+        return Ok(());
+    }
+    if cli.daemon {
+        #[cfg(unix)]
+        {
+            let socket_path = infrastructure::config::get_socket_path(&config);
+            interfaces::daemon::run(state, &socket_path, shutdown_rx).await?;
+            return Ok(());
+        }
+        #[cfg(not(unix))]
+        anyhow::bail!("Daemon mode is only supported on Unix systems");
+    }
 
     // Handle query
     if cli.query.is_empty() {
@@ -94,6 +197,74 @@ async fn main() -> anyhow::Result<()> {
     let query = cli.query.join(" ");
     let result = application::query::query_word(&state, &query, cli.nocache, cli.text).await?;
 
+    print_result(&cli, &config, &result)?;
+    // Let a stale entry's background refresh finish before exiting
+    state.drain_refreshes().await;
+    Ok(())
+}
+
+/// Whether the command line is a plain lookup (no other command flags)
+#[cfg(unix)]
+fn is_plain_query(cli: &Cli) -> bool {
+    cli.command.is_none()
+        && !cli.query.is_empty()
+        && !cli.update_dict
+        && !cli.generate_config
+        && !cli.edit_config
+        && !cli.status
+        && !cli.interactive
+        && cli.batch.is_none()
+        && cli.search.is_none()
+        // The daemon translates with its own configured languages
+        && cli.from.is_none()
+        && cli.to.is_none()
+}
+
+/// Format search hits as a numbered list
+fn format_search_hits(
+    pattern: &str,
+    hits: &[domain::model::SearchHit],
+    theme: &presentation::theme::Theme,
+) -> String {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    if hits.is_empty() {
+        writeln!(
+            output,
+            "{}",
+            format!("No entries match \"{}\"", pattern).red()
+        )
+        .ok();
+        return output;
+    }
+
+    let width = hits
+        .iter()
+        .map(|hit| hit.query.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (i, hit) in hits.iter().enumerate() {
+        let padding = " ".repeat(width - hit.query.chars().count());
+        writeln!(
+            output,
+            "{} {}{}  {}",
+            (theme.idx)(&format!("{:>3}.", i + 1)),
+            hit.query.bold(),
+            padding,
+            (theme.para)(&hit.summary)
+        )
+        .ok();
+    }
+    output
+}
+
+/// Render a query result to stdout according to CLI flags and config
+fn print_result(
+    cli: &Cli,
+    config: &infrastructure::config::Config,
+    result: &domain::model::QueryResult,
+) -> anyhow::Result<()> {
     // Load theme
     let theme_name = cli.theme.as_deref().unwrap_or(config.theme.as_str());
     let theme = presentation::theme::Theme::from_name(theme_name);
@@ -105,14 +276,14 @@ async fn main() -> anyhow::Result<()> {
 
     // Check frequency alert if configured
     if config.freq_alert {
-        check_frequency_alert(&state).await?;
+        check_frequency_alert();
     }
 
     // Output result
     if cli.json {
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        println!("{}", serde_json::to_string_pretty(result)?);
     } else {
-        let output = format_result(&result, &theme, config.english_only, config.enable_emoji);
+        let output = format_result(result, &theme, config.english_only, config.enable_emoji);
 
         // Use pager if configured
         if config.paging {
@@ -125,16 +296,6 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Check if a query string is English (only contains letters, numbers, spaces, hyphens, dots, question marks)
-fn is_english_query(query: &str) -> bool {
-    // Match pattern: ^[A-Za-z0-9 -.?]+$
-    // Only contains: letters, numbers, spaces, hyphens, dots, question marks
-    query
-        .chars()
-        .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '.' || c == '?')
-        && !query.is_empty()
-}
-
 /// Filter translations to only English ones (for english_only mode)
 fn filter_english_translations(translations: &[String]) -> Vec<String> {
     translations
@@ -200,7 +361,7 @@ fn init_logging(logging: &infrastructure::config::Logging) -> anyhow::Result<()>
 }
 
 /// Check query frequency and alert if too high
-async fn check_frequency_alert(_state: &AppState) -> anyhow::Result<()> {
+fn check_frequency_alert() {
     use once_cell::sync::Lazy;
     use std::collections::VecDeque;
     use std::sync::Mutex;
@@ -221,13 +382,11 @@ async fn check_frequency_alert(_state: &AppState) -> anyhow::Result<()> {
     // Check if frequency is too high (more than 30 queries per minute)
     if history.len() >= 30 {
         eprintln!("{}", "⚠️  查询频率过高，请稍后再试".yellow());
-        return Ok(());
+        return;
     }
 
     // Add current query to history
     history.push_back(now);
-
-    Ok(())
 }
 
 /// Format result as string (for pager support)
@@ -241,7 +400,7 @@ fn format_result(
 
     let mut output = String::new();
     // Check if query is English (used for english_only mode)
-    let is_english = is_english_query(&result.query);
+    let is_english = result.detected_lang.as_deref() == Some("en");
 
     // Query word with source indicator
     let source_indicator = match &result.source {
@@ -266,13 +425,39 @@ fn format_result(
                 "[在线]"
             }
         }
+        domain::model::QuerySource::Suggestions => {
+            if enable_emoji {
+                "🔍 [建议]"
+            } else {
+                "[建议]"
+            }
+        }
     };
-    writeln!(
-        output,
-        "{} {}",
-        (theme.title)(&result.query),
-        source_indicator.cyan()
-    )
+    // An expired cache entry is still shown while it refreshes in the background
+    let source_indicator = if result.stale {
+        format!("{} [已过期]", source_indicator)
+    } else {
+        source_indicator.to_string()
+    };
+    // Show the lemma an inflected form resolved to, e.g. "running → run"
+    let title = match &result.headword {
+        Some(headword) => format!(
+            "{} → {}",
+            (theme.title)(&result.query),
+            (theme.title)(headword)
+        ),
+        None => (theme.title)(&result.query),
+    };
+    match &result.lang_pair {
+        Some(pair) => writeln!(
+            output,
+            "{} {} {}",
+            title,
+            format!("{} → {}", pair.from, pair.to).yellow(),
+            source_indicator.cyan()
+        ),
+        _ => writeln!(output, "{} {}", title, source_indicator.cyan()),
+    }
     .ok();
 
     // Pronunciation (US/UK)
@@ -318,6 +503,20 @@ fn format_result(
         }
     }
 
+    // Did-you-mean suggestions for a miss, or other candidates for a pinyin lookup
+    if !result.suggestions.is_empty() {
+        let label = if result.found {
+            "其他候选："
+        } else {
+            "未找到该词，您是不是要找："
+        };
+        writeln!(output).ok();
+        writeln!(output, "  {}", label.yellow()).ok();
+        for (i, word) in result.suggestions.iter().enumerate() {
+            writeln!(output, "  {}. {}", (theme.idx)(&(i + 1).to_string()), word).ok();
+        }
+    }
+
     // Collins rank
     if let Some(rank) = &result.collins_rank {
         writeln!(output, "  {}", (theme.rank)(rank)).ok();
@@ -479,14 +678,37 @@ async fn print_status(state: &AppState) -> anyhow::Result<()> {
     drop(config);
 
     if db_path.exists() {
-        let count = sqlite3_count(&state.db).await?;
+        let count = infrastructure::storage::db::count_cache(&state.db).await?;
         println!("Database: {} ({} records)", db_path.display(), count);
     } else {
         println!("Database: Not initialized");
     }
 
     // Cache status
-    println!("Memory Cache: {} entries", state.cache.len());
+    println!(
+        "Memory Cache: {}",
+        interfaces::cache::format_memory_cache(&state.cache.stats())
+    );
+
+    // Daemon status
+    #[cfg(unix)]
+    {
+        use interfaces::daemon::{DaemonRequest, DaemonResponse};
+
+        let config = state.config.read().await;
+        let socket_path = infrastructure::config::get_socket_path(&config);
+        drop(config);
+
+        match interfaces::daemon::send(&socket_path, &DaemonRequest::Status).await {
+            Some(Ok(DaemonResponse::Status { pid, cache })) => println!(
+                "Daemon: running (pid {}, cache: {})",
+                pid,
+                interfaces::cache::format_memory_cache(&cache)
+            ),
+            Some(_) => println!("Daemon: not responding"),
+            None => println!("Daemon: not running"),
+        }
+    }
 
     // Config status
     let config = state.config.read().await;
@@ -503,15 +725,25 @@ async fn print_status(state: &AppState) -> anyhow::Result<()> {
         println!("Youdao API: Not configured");
     }
 
-    Ok(())
-}
-
-async fn sqlite3_count(db: &tokio_rusqlite::Connection) -> anyhow::Result<usize> {
-    use tokio_rusqlite::params;
-
-    let count: i64 = db
-        .call(|conn| conn.query_row("SELECT COUNT(*) FROM cache", params![], |row| row.get(0)))
-        .await?;
+    use infrastructure::network::provider::ProviderChain;
+    let chains = [
+        (
+            "Providers",
+            ProviderChain::from_config(&state.http_client, &config),
+        ),
+        (
+            "Long Text Providers",
+            ProviderChain::for_long_text(&state.http_client, &config),
+        ),
+    ];
+    for (label, chain) in chains {
+        let names = chain.names();
+        if names.is_empty() {
+            println!("{}: None enabled", label);
+        } else {
+            println!("{}: {}", label, names.join(" → "));
+        }
+    }
 
-    Ok(count as usize)
+    Ok(())
 }
@@ -0,0 +1,107 @@
+// Rewrite cache keys written before (or under different) normalization settings
+use crate::domain::error::KdError;
+use crate::domain::normalize::NormalizeOptions;
+use crate::infrastructure::storage::codec;
+use crate::infrastructure::storage::db::{
+    get_meta, set_meta, source_language, OFFLINE_SOURCE, ZH_NAMESPACE,
+};
+use crate::infrastructure::storage::pinyin;
+use rusqlite::{params, OptionalExtension};
+use tokio_rusqlite::Connection;
+
+const FINGERPRINT_KEY: &str = "normalization";
+
+/// Bring every cache key in line with `options`
+///
+/// Runs only when the settings differ from the ones the keys were last
+/// normalized with. An online row whose normalized key already exists is
+/// dropped in favour of the existing row; an offline row keeps its own key.
+/// Returns the number of rows rewritten or dropped.
+pub async fn normalize_cache_keys(
+    db: &Connection,
+    options: &NormalizeOptions,
+) -> Result<usize, KdError> {
+    let fingerprint = options.fingerprint();
+    if get_meta(db, FINGERPRINT_KEY).await?.as_deref() == Some(fingerprint.as_str()) {
+        return Ok(0);
+    }
+
+    let options = options.clone();
+    let changed = db
+        .call(move |conn| {
+            let tx = conn.transaction()?;
+            let rows: Vec<(i64, String, String, String)> = {
+                let mut stmt = tx.prepare("SELECT rowid, ns, query, source FROM cache")?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })?;
+                rows.collect::<Result<_, _>>()?
+            };
+
+            let mut changed = 0;
+            for (rowid, ns, query, source) in rows {
+                let options = options.for_source(source_language(&ns));
+                let long_text_options = options.for_long_text();
+                let mut key = options.normalize(&query);
+                if key == query {
+                    continue;
+                }
+                // Long text keeps its case; only rows folding would change need decoding
+                let unfolded = long_text_options.normalize(&query);
+                if key != unfolded && is_long_text(&tx, rowid)? {
+                    key = unfolded;
+                    if key == query {
+                        continue;
+                    }
+                }
+                let existing: Option<i64> = tx
+                    .query_row(
+                        "SELECT rowid FROM cache WHERE ns = ? AND query = ?",
+                        params![ns, key],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                if existing.is_some() {
+                    // Dictionary headwords told apart by case ("US" and "us") both stay;
+                    // the other is looked up when typed in its own case
+                    if source == OFFLINE_SOURCE {
+                        continue;
+                    }
+                    tx.execute("DELETE FROM cache WHERE rowid = ?", params![rowid])?;
+                } else {
+                    tx.execute(
+                        "UPDATE cache SET query = ? WHERE rowid = ?",
+                        params![key, rowid],
+                    )?;
+                    tx.execute(
+                        "UPDATE cache_fts SET query = ? WHERE rowid = ?",
+                        params![key, rowid],
+                    )?;
+                    if ns == ZH_NAMESPACE {
+                        pinyin::index_entry(&tx, rowid, &key)?;
+                    }
+                }
+                changed += 1;
+            }
+
+            tx.commit()?;
+            Ok(changed)
+        })
+        .await?;
+
+    set_meta(db, FINGERPRINT_KEY, &fingerprint).await?;
+    if changed > 0 {
+        tracing::info!("Normalized {} cache keys ({})", changed, fingerprint);
+    }
+
+    Ok(changed)
+}
+
+fn is_long_text(conn: &rusqlite::Connection, rowid: i64) -> rusqlite::Result<bool> {
+    let data: Vec<u8> = conn.query_row(
+        "SELECT data FROM cache WHERE rowid = ?",
+        params![rowid],
+        |row| row.get(0),
+    )?;
+    Ok(codec::decode(&data).is_ok_and(|result| result.is_long_text))
+}
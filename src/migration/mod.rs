@@ -1,3 +1,4 @@
-// Migration layer: Legacy data migration
+// Migration layer: Legacy data and cache key migration
 
+pub mod keys;
 pub mod legacy;
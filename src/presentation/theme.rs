@@ -17,15 +17,23 @@ pub struct Theme {
 }
 
 impl Theme {
+    /// Names accepted by `named`
+    pub const NAMES: &'static [&'static str] = &["temp", "wudao", "canvas"];
+
     pub fn from_name(name: &str) -> Self {
+        Self::named(name).unwrap_or_else(|| {
+            eprintln!("{}", format!("✘ Unknown theme: {}", name).red());
+            Self::temp() // Fallback to default
+        })
+    }
+
+    /// The theme called `name`, `None` when there is no such theme
+    pub fn named(name: &str) -> Option<Self> {
         match name {
-            "temp" | "" => Self::temp(),
-            "wudao" => Self::wudao(),
-            "canvas" => Self::canvas(),
-            _ => {
-                eprintln!("{}", format!("✘ Unknown theme: {}", name).red());
-                Self::temp() // Fallback to default
-            }
+            "temp" | "" => Some(Self::temp()),
+            "wudao" => Some(Self::wudao()),
+            "canvas" => Some(Self::canvas()),
+            _ => None,
         }
     }
 
@@ -1,18 +1,33 @@
 use crate::domain::error::KdError;
-use crate::domain::model::QueryResult;
-use crate::infrastructure::config::Config;
+use crate::domain::spelling::BkTree;
+use crate::domain::traits::Cache;
+use crate::infrastructure::config::{CacheTtl, Config};
+use crate::infrastructure::network::template;
+use crate::infrastructure::storage::cache::MemoryCache;
 use dashmap::DashMap;
 use reqwest::Client;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock as SyncRwLock};
+use tokio::sync::{OnceCell, RwLock};
+use tokio::task::JoinSet;
 use tokio_rusqlite::Connection;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: Arc<Connection>,
-    pub cache: Arc<DashMap<String, QueryResult>>,
+    /// Recently used results, bounded by `[cache]` memory_entries and memory_size
+    pub cache: Arc<dyn Cache>,
     pub config: Arc<RwLock<Config>>,
+    /// `[cache]` TTLs, parsed once so a typo fails at startup rather than on a lookup
+    pub ttl: Arc<CacheTtl>,
     pub http_client: Client,
+    /// Headword index for spelling suggestions, built on the first miss
+    ///
+    /// Only long-running modes keep one; a single lookup asks the database instead.
+    pub suggestions: Option<Arc<OnceCell<SyncRwLock<BkTree>>>>,
+    /// Background refreshes of stale cache entries, keyed by the entry being refreshed
+    refreshing: Arc<DashMap<String, ()>>,
+    refreshes: Arc<Mutex<JoinSet<()>>>,
 }
 
 impl AppState {
@@ -37,13 +52,55 @@ impl AppState {
             }
         }
 
+        // Reject malformed field expressions now rather than on every lookup
+        for template in &config.http_templates {
+            template::validate(template)?;
+        }
+
         let http_client = client_builder.build()?;
+        let cache = MemoryCache::new(config.cache.memory_entries, config.cache.memory_bytes()?);
+        let ttl = config.cache.ttl()?;
 
         Ok(Self {
             db: Arc::new(db),
-            cache: Arc::new(DashMap::new()),
+            cache: Arc::new(cache),
             config: Arc::new(RwLock::new(config)),
+            ttl: Arc::new(ttl),
             http_client,
+            suggestions: None,
+            refreshing: Arc::new(DashMap::new()),
+            refreshes: Arc::new(Mutex::new(JoinSet::new())),
         })
     }
+
+    /// Keep an in-memory spelling index, for modes that serve many lookups
+    pub fn with_suggestion_index(mut self) -> Self {
+        self.suggestions = Some(Arc::new(OnceCell::new()));
+        self
+    }
+
+    /// Run `task` in the background unless a refresh of `key` is already running
+    pub fn spawn_refresh<F>(&self, key: String, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if self.refreshing.insert(key.clone(), ()).is_some() {
+            return;
+        }
+        let refreshing = self.refreshing.clone();
+        let mut refreshes = self.refreshes.lock().unwrap_or_else(|e| e.into_inner());
+        // Long-running modes never drain, so reap finished refreshes as new ones start
+        while refreshes.try_join_next().is_some() {}
+        refreshes.spawn(async move {
+            task.await;
+            refreshing.remove(&key);
+        });
+    }
+
+    /// Wait for background refreshes to finish, so a one-shot command doesn't drop them
+    pub async fn drain_refreshes(&self) {
+        let mut refreshes =
+            std::mem::take(&mut *self.refreshes.lock().unwrap_or_else(|e| e.into_inner()));
+        while refreshes.join_next().await.is_some() {}
+    }
 }
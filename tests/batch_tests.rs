@@ -0,0 +1,143 @@
+//! 批量查询测试（并发查询、保持输入顺序、多种输出格式）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+use std::io::Write;
+use std::process::Stdio;
+use std::time::Duration;
+
+const WORDS: &str = "slow\nhello\n# comment\n\nmissing\napple\n";
+
+/// "slow" 由在线查询源延迟返回，其他词在线查不到
+fn batch_sandbox() -> (Sandbox, StubServer) {
+    let server = StubServer::start(|request| {
+        if request.path.contains("q=slow") {
+            std::thread::sleep(Duration::from_millis(300));
+            StubResponse::json(json!({"value": [{"meaningGroups": [{
+                "partsOfSpeech": [{"name": "adj.", "description": "快速释义"}],
+                "meanings": [{"richDefinitions": [{"fragments": [{"text": "慢的"}]}]}]
+            }]}]}))
+        } else {
+            StubResponse::json(json!({"value": []}))
+        }
+    });
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"bing\"]\n\n[bing]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+    (sandbox, server)
+}
+
+#[test]
+fn test_batch_jsonl_keeps_input_order() {
+    let (sandbox, _server) = batch_sandbox();
+    let list = sandbox.kd_dir().join("words.txt");
+    std::fs::write(&list, WORDS).unwrap();
+
+    let output = sandbox.run(&[
+        "--batch",
+        list.to_str().unwrap(),
+        "--format",
+        "jsonl",
+        "-j",
+        "4",
+    ]);
+    assert!(output.status.success());
+
+    let lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    let queries: Vec<_> = lines.iter().map(|l| l["query"].as_str().unwrap()).collect();
+    assert_eq!(queries, ["slow", "hello", "missing", "apple"]);
+    assert_eq!(lines[0]["source"], json!({"Online": "Bing"}));
+    assert_eq!(lines[1]["source"], "OfflineDb");
+    assert_eq!(lines[2]["found"], false);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("4 words: 3 found, 1 not found, 0 errors"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("bing 1, offline 2"), "{}", stderr);
+}
+
+#[test]
+fn test_batch_csv_from_stdin() {
+    let (sandbox, _server) = batch_sandbox();
+
+    let mut child = sandbox
+        .kd()
+        .args(["--batch", "-", "--format", "csv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"hello\napple\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(
+        lines[0],
+        "query,found,source,pronunciation,translations,error"
+    );
+    assert_eq!(lines[1], "hello,true,offline,,int. 你好,");
+    assert_eq!(lines[2], "apple,true,offline,,n. 苹果,");
+}
+
+#[test]
+fn test_batch_reports_errors_per_word() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+    let list = sandbox.kd_dir().join("words.txt");
+    std::fs::write(&list, "hello\nunknown\n").unwrap();
+
+    let output = sandbox.run(&["--json", "--batch", list.to_str().unwrap()]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines[1]["query"], "unknown");
+    assert!(lines[1]["error"]
+        .as_str()
+        .unwrap()
+        .contains("No online provider enabled"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 found, 0 not found, 1 errors"));
+}
+
+#[test]
+fn test_batch_explicit_pretty_format_wins_over_json() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+    let list = sandbox.kd_dir().join("words.txt");
+    std::fs::write(&list, "hello\n").unwrap();
+
+    let output = sandbox.run(&[
+        "--json",
+        "--batch",
+        list.to_str().unwrap(),
+        "--format",
+        "pretty",
+    ]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("int. 你好"), "{}", stdout);
+    assert!(serde_json::from_str::<serde_json::Value>(stdout.trim()).is_err());
+}
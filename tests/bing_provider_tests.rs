@@ -0,0 +1,96 @@
+//! Bing 词典查询源测试（使用本地桩服务器）
+
+mod common;
+
+use common::{Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+fn group(name: &str, description: &str, meanings: serde_json::Value) -> serde_json::Value {
+    json!({
+        "partsOfSpeech": [{"name": name, "description": description}],
+        "meanings": meanings,
+    })
+}
+
+fn fragments(text: &str) -> serde_json::Value {
+    json!([{"richDefinitions": [{"fragments": [{"text": text}]}]}])
+}
+
+fn bing_hello() -> serde_json::Value {
+    json!({
+        "value": [{
+            "name": "hello",
+            "meaningGroups": [
+                group("美", "发音", fragments("heˈləʊ")),
+                group("英", "发音", fragments("həˈləʊ")),
+                group("int.", "快速释义", fragments("你好；喂")),
+                group("n.", "快速释义", fragments("招呼；问候")),
+                group("", "例句", json!([{"richDefinitions": [{"examples": [
+                    "Hello, John! How are you?", "你好，约翰！你好吗？"
+                ]}]}])),
+            ]
+        }]
+    })
+}
+
+fn bing_sandbox(server: &StubServer) -> Sandbox {
+    Sandbox::new(&format!(
+        r#"providers = ["bing"]
+
+[bing]
+endpoint = "{}"
+"#,
+        server.url()
+    ))
+}
+
+#[test]
+fn test_bing_fills_query_result() {
+    let server = StubServer::start(|_| StubResponse::json(bing_hello()));
+    let sandbox = bing_sandbox(&server);
+
+    let result = sandbox.query_json(&["--nocache", "hello"]);
+
+    assert_eq!(result["found"], true);
+    assert_eq!(result["pronunciation_us"], "heˈləʊ");
+    assert_eq!(result["pronunciation_uk"], "həˈləʊ");
+    assert_eq!(
+        result["translations"],
+        json!(["int. 你好；喂", "n. 招呼；问候"])
+    );
+    assert_eq!(
+        result["examples"],
+        json!([["Hello, John! How are you?", "你好，约翰！你好吗？"]])
+    );
+    assert_eq!(result["source"], json!({"Online": "Bing"}));
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0]
+        .path
+        .starts_with("/api/v7/dictionarywords/search?"));
+    assert!(requests[0].path.contains("q=hello"));
+}
+
+#[test]
+fn test_bing_empty_response_is_not_found() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"value": []})));
+    let sandbox = bing_sandbox(&server);
+
+    let result = sandbox.query_json(&["--nocache", "qwertyuiop"]);
+
+    assert_eq!(result["found"], false);
+    assert_eq!(result["source"], json!({"Online": "Bing"}));
+}
+
+#[test]
+fn test_bing_http_error_is_reported() {
+    let server = StubServer::start(|_| StubResponse::status(503));
+    let sandbox = bing_sandbox(&server);
+
+    let output = sandbox.run(&["--json", "--nocache", "hello"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("bing"), "unexpected stderr: {}", stderr);
+}
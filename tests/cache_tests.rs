@@ -0,0 +1,439 @@
+//! 缓存测试（有效期、过期标记与后台刷新，以及 kd cache 管理子命令）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// 每次请求返回递增的译文：译文1、译文2……
+fn counting_server() -> StubServer {
+    let count = AtomicUsize::new(0);
+    StubServer::start(move |_| {
+        let n = count.fetch_add(1, Ordering::SeqCst) + 1;
+        StubResponse::json(json!({"translatedText": format!("译文{}", n)}))
+    })
+}
+
+fn cache_sandbox(server: &StubServer, cache: &str) -> Sandbox {
+    Sandbox::new(&format!(
+        "providers = [\"libretranslate\"]\n\n[libretranslate]\nendpoint = \"{}\"\n\n[cache]\n{}",
+        server.url(),
+        cache
+    ))
+}
+
+/// 写入一条 age 秒之前从 LibreTranslate 查到的在线结果
+fn seed_online(sandbox: &Sandbox, word: &str, translation: &str, age: i64) {
+    let mut result = entry(word, &[translation]);
+    result["source"] = json!({"Online": "LibreTranslate"});
+    result["cached_at"] = json!(unix_now() - age);
+    sandbox.seed(word, &result);
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[test]
+fn test_stale_entry_is_served_then_refreshed() {
+    let server = counting_server();
+    let sandbox = cache_sandbox(&server, "ttl = \"1d\"\n");
+    seed_online(&sandbox, "hello", "旧译文", 2 * DAY);
+
+    let stale = sandbox.query_json(&["hello"]);
+    assert_eq!(stale["translations"], json!(["旧译文"]));
+    assert_eq!(stale["stale"], true);
+    // 进程退出前完成后台刷新
+    assert_eq!(server.requests().len(), 1);
+
+    let fresh = sandbox.query_json(&["hello"]);
+    assert_eq!(fresh["translations"], json!(["译文1"]));
+    assert_eq!(fresh["stale"], false);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn test_stale_marker_in_header() {
+    let server = counting_server();
+    let sandbox = cache_sandbox(&server, "ttl = \"1d\"\n");
+    seed_online(&sandbox, "hello", "旧译文", 2 * DAY);
+
+    let output = sandbox.run(&["hello"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(
+        stdout.lines().next().unwrap().contains("[已过期]"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_fresh_entry_is_not_refreshed() {
+    let server = counting_server();
+    let sandbox = cache_sandbox(&server, "ttl = \"1d\"\n");
+    seed_online(&sandbox, "hello", "旧译文", 60);
+
+    let result = sandbox.query_json(&["hello"]);
+
+    assert_eq!(result["stale"], false);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_ttl_per_source() {
+    let server = counting_server();
+    let sandbox = cache_sandbox(
+        &server,
+        "ttl = \"1d\"\n\n[cache.source_ttl]\nlibretranslate = \"never\"\n",
+    );
+    seed_online(&sandbox, "hello", "旧译文", 400 * DAY);
+
+    let result = sandbox.query_json(&["hello"]);
+
+    assert_eq!(result["stale"], false);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_offline_entries_never_expire() {
+    let server = counting_server();
+    let sandbox = cache_sandbox(&server, "ttl = \"0\"\n");
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    let result = sandbox.query_json(&["apple"]);
+
+    assert_eq!(result["source"], "OfflineDb");
+    assert_eq!(result["stale"], false);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_invalid_ttl_is_reported_at_startup() {
+    let server = counting_server();
+    // 无需命中过期条目：启动时即解析全部 TTL
+    for extra in ["ttl = \"soon\"\n", "[cache.source_ttl]\nbing = \"soon\"\n"] {
+        let sandbox = cache_sandbox(&server, extra);
+
+        let output = sandbox.run(&["--status"]);
+
+        assert!(!output.status.success(), "{}", extra);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("invalid duration \"soon\""));
+    }
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_invalid_memory_size_is_reported() {
+    let sandbox = Sandbox::new("providers = []\n\n[cache]\nmemory_size = \"lots\"\n");
+
+    let output = sandbox.run(&["hello"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid size \"lots\""));
+}
+
+#[test]
+fn test_prune_removes_old_online_results_only() {
+    let server = counting_server();
+    let sandbox = cache_sandbox(&server, "");
+    sandbox.query_json(&["old"]);
+    sandbox.query_json(&["recent"]);
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    conn.execute(
+        "UPDATE cache SET updated_at = ?1 WHERE query = 'old'",
+        [unix_now() - 40 * DAY],
+    )
+    .unwrap();
+    let sources: Vec<String> = conn
+        .prepare("SELECT source FROM cache ORDER BY query")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(sources, ["offline", "libretranslate", "libretranslate"]);
+
+    let output = sandbox.run(&["cache", "prune", "--older-than", "30d"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Pruned 1"));
+
+    let remaining: Vec<String> = conn
+        .prepare("SELECT query FROM cache ORDER BY query")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    // 离线词条的 updated_at 为 0，但不会被清理
+    assert_eq!(remaining, ["apple", "recent"]);
+}
+
+#[test]
+fn test_prune_rejects_bad_duration() {
+    let sandbox = Sandbox::new("providers = []\n");
+
+    let output = sandbox.run(&["cache", "prune", "--older-than", "3x"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid duration"));
+}
+
+#[test]
+fn test_source_column_backfilled_for_old_tables() {
+    let sandbox = Sandbox::new("providers = []\n");
+    std::fs::create_dir_all(sandbox.kd_dir()).unwrap();
+
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE cache (
+            ns TEXT NOT NULL DEFAULT 'en',
+            query TEXT NOT NULL,
+            data BLOB NOT NULL,
+            compressed_size INTEGER NOT NULL,
+            original_size INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (ns, query)
+        );",
+    )
+    .unwrap();
+    // 批量查询在同一秒写入的大量在线结果也要识别出来
+    let mut rows = vec![("apple".to_string(), entry("apple", &["n. 苹果"]))];
+    for i in 0..150 {
+        let word = format!("word{}", i);
+        let mut online = entry(&word, &["释义"]);
+        online["source"] = json!({"Online": "Bing"});
+        rows.push((word, online));
+    }
+    for (word, result) in rows {
+        let data = serde_json::to_vec(&result).unwrap();
+        let compressed = zstd::encode_all(&data[..], 0).unwrap();
+        conn.execute(
+            "INSERT INTO cache (query, data, compressed_size, original_size, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, 0, 0)",
+            rusqlite::params![word, compressed, compressed.len(), data.len()],
+        )
+        .unwrap();
+    }
+    drop(conn);
+
+    let output = sandbox.run(&["cache", "prune", "--older-than", "1d"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Pruned 150"));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+/// 查询两个在线词条（一个放到 40 天前）并写入一个离线词条
+fn mixed_cache(server: &StubServer) -> Sandbox {
+    let sandbox = cache_sandbox(server, "");
+    sandbox.query_json(&["old"]);
+    sandbox.query_json(&["recent"]);
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+    rusqlite::Connection::open(sandbox.db_path())
+        .unwrap()
+        .execute(
+            "UPDATE cache SET updated_at = ?1 WHERE query = 'old'",
+            [unix_now() - 40 * DAY],
+        )
+        .unwrap();
+    sandbox
+}
+
+fn cache_json(sandbox: &Sandbox, args: &[&str]) -> serde_json::Value {
+    let output = sandbox.run(args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn listed(sandbox: &Sandbox, args: &[&str]) -> Vec<String> {
+    let mut command = vec!["cache", "ls", "--json"];
+    command.extend_from_slice(args);
+    cache_json(sandbox, &command)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["query"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_ls_filters_by_source_and_date() {
+    let server = counting_server();
+    let sandbox = mixed_cache(&server);
+
+    // 按更新时间倒序，离线词条的 updated_at 为 0
+    assert_eq!(listed(&sandbox, &[]), ["recent", "old", "apple"]);
+    assert_eq!(
+        listed(&sandbox, &["--source", "libretranslate"]),
+        ["recent", "old"]
+    );
+    assert_eq!(listed(&sandbox, &["--source", "offline"]), ["apple"]);
+    assert_eq!(listed(&sandbox, &["--since", "7d"]), ["recent"]);
+    assert_eq!(
+        listed(&sandbox, &["--until", "7d", "--source", "libretranslate"]),
+        ["old"]
+    );
+    assert_eq!(
+        listed(&sandbox, &["--since", "2000-01-01"]),
+        ["recent", "old"]
+    );
+    assert_eq!(listed(&sandbox, &["--limit", "1"]), ["recent"]);
+
+    let entries = cache_json(&sandbox, &["cache", "ls", "--json", "--source", "offline"]);
+    assert_eq!(entries[0]["ns"], "en");
+    assert!(entries[0]["compressed_size"].as_i64().unwrap() > 0);
+}
+
+#[test]
+fn test_ls_prints_one_line_per_entry() {
+    let server = counting_server();
+    let sandbox = mixed_cache(&server);
+
+    let output = sandbox.run(&["cache", "ls"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(stdout.lines().count(), 3, "{}", stdout);
+    assert!(stdout.lines().next().unwrap().contains("libretranslate"));
+    assert!(stdout.lines().last().unwrap().starts_with('-'));
+}
+
+#[test]
+fn test_ls_rejects_bad_date() {
+    let sandbox = Sandbox::new("providers = []\n");
+
+    let output = sandbox.run(&["cache", "ls", "--since", "yesterday"]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_show_prints_decoded_entries() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+    sandbox.seed_ns("en-ja", "apple", &entry("apple", &["りんご"]));
+
+    let entries = cache_json(&sandbox, &["cache", "show", "Apple"]);
+    assert_eq!(entries.as_array().unwrap().len(), 2);
+    assert_eq!(entries[0]["ns"], "en");
+    assert_eq!(entries[0]["source"], "offline");
+    assert_eq!(entries[0]["data"]["translations"], json!(["n. 苹果"]));
+    assert_eq!(entries[1]["ns"], "en-ja");
+
+    let entries = cache_json(&sandbox, &["cache", "show", "apple", "--ns", "en-ja"]);
+    assert_eq!(entries[0]["data"]["translations"], json!(["りんご"]));
+
+    let output = sandbox.run(&["cache", "show", "pear"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No cached entry"));
+}
+
+#[test]
+fn test_rm_by_word_and_glob() {
+    let sandbox = Sandbox::new("providers = []\n");
+    for word in ["apple", "application", "apply", "banana", "PHP"] {
+        sandbox.seed(word, &entry(word, &["释义"]));
+    }
+
+    // 单词按查询时的规则归一化
+    let output = sandbox.run(&["cache", "rm", "Banana", "--yes"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed 1"));
+
+    // 通配符原样匹配，不做大小写折叠
+    let output = sandbox.run(&["cache", "rm", "[A-Z]*", "--yes"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed 1"));
+
+    let output = sandbox.run(&["cache", "rm", "appl*", "--source", "offline", "--yes"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed 3"));
+
+    assert!(listed(&sandbox, &[]).is_empty());
+    // 全文索引随之删除
+    let output = sandbox.run(&["search", "释义"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("apple"));
+}
+
+#[test]
+fn test_rm_respects_filters() {
+    let server = counting_server();
+    let sandbox = mixed_cache(&server);
+
+    let output = sandbox.run(&[
+        "cache",
+        "rm",
+        "*",
+        "--source",
+        "libretranslate",
+        "--until",
+        "7d",
+    ]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed 1"));
+
+    assert_eq!(listed(&sandbox, &[]), ["recent", "apple"]);
+}
+
+#[test]
+fn test_rm_requires_yes_for_offline_entries() {
+    let server = counting_server();
+    let sandbox = mixed_cache(&server);
+
+    let output = sandbox.run(&["cache", "rm", "*"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pass --yes"));
+    assert_eq!(listed(&sandbox, &[]).len(), 3);
+
+    // 只删除在线结果时无需确认
+    let output = sandbox.run(&["cache", "rm", "*", "--source", "libretranslate"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed 2"));
+
+    let output = sandbox.run(&["cache", "rm", "*", "--yes"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed 1"));
+    assert!(listed(&sandbox, &[]).is_empty());
+}
+
+#[test]
+fn test_vacuum_reports_sizes() {
+    let sandbox = Sandbox::new("providers = []\n");
+    for i in 0..200 {
+        let word = format!("word{}", i);
+        sandbox.seed(&word, &entry(&word, &["释义"]));
+    }
+    sandbox.run(&["cache", "rm", "word*", "--yes"]);
+
+    let output = sandbox.run(&["cache", "vacuum"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(stdout.contains("Database compacted"), "{}", stdout);
+}
+
+#[test]
+fn test_stats_counts_sources_and_sizes() {
+    let server = counting_server();
+    let sandbox = mixed_cache(&server);
+
+    let stats = cache_json(&sandbox, &["cache", "stats", "--json"]);
+
+    assert_eq!(stats["entries"], 3);
+    assert_eq!(
+        stats["by_source"],
+        json!({"libretranslate": 2, "offline": 1})
+    );
+    assert_eq!(stats["by_namespace"], json!({"en": 3}));
+    assert!(stats["compressed_size"].as_i64().unwrap() > 0);
+    assert!(stats["original_size"].as_i64().unwrap() > 0);
+
+    let output = sandbox.run(&["cache", "stats"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Compression ratio"), "{}", stdout);
+    assert!(stdout.contains("libretranslate  2"), "{}", stdout);
+}
@@ -0,0 +1,185 @@
+//! 汉英查询测试（中文词条命名空间、拼音输入、繁简转换）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+fn chinese_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed_ns("zh", "电脑", &entry("电脑", &["n. computer"]));
+    sandbox.seed_ns("zh", "实验", &entry("实验", &["n. experiment"]));
+    sandbox.seed_ns("zh", "试验", &entry("试验", &["n. test; trial"]));
+    sandbox.seed_ns("zh", "誓言", &entry("誓言", &["n. oath; pledge"]));
+    sandbox.seed_ns("zh", "银行", &entry("银行", &["n. bank"]));
+    sandbox.seed("computer", &entry("computer", &["n. 计算机；电脑"]));
+    sandbox
+}
+
+#[test]
+fn test_chinese_headword_lookup() {
+    let sandbox = chinese_sandbox();
+
+    let result = sandbox.query_json(&["电脑"]);
+    assert_eq!(result["found"], true);
+    assert_eq!(result["translations"], json!(["n. computer"]));
+
+    // 繁体输入转换为简体后查询，显示保留原输入
+    let result = sandbox.query_json(&["電腦"]);
+    assert_eq!(result["found"], true);
+    assert_eq!(result["query"], "電腦");
+    assert_eq!(result["translations"], json!(["n. computer"]));
+
+    let result = sandbox.query_json(&["銀行"]);
+    assert_eq!(result["translations"], json!(["n. bank"]));
+}
+
+#[test]
+fn test_pinyin_input_resolves_to_headword() {
+    let sandbox = chinese_sandbox();
+
+    for input in ["diannao", "dian nao", "dian4nao3", "diàn nǎo", "DianNao"] {
+        let result = sandbox.query_json(&[input]);
+        assert_eq!(result["found"], true, "{}", input);
+        assert_eq!(result["query"], input.trim(), "{}", input);
+        assert_eq!(result["headword"], "电脑", "{}", input);
+        assert_eq!(result["translations"], json!(["n. computer"]));
+    }
+
+    // 多音字：银行读作 yin hang
+    let result = sandbox.query_json(&["yinhang"]);
+    assert_eq!(result["headword"], "银行");
+
+    // 英文词条优先于拼音
+    let result = sandbox.query_json(&["computer"]);
+    assert_eq!(result["headword"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_english_words_that_read_as_pinyin() {
+    let server = StubServer::start(|request| {
+        if request.path.contains("q=nuoxin") {
+            return StubResponse::json(json!({"value": []}));
+        }
+        StubResponse::json(json!({"value": [{"meaningGroups": [{
+            "partsOfSpeech": [{"name": "n.", "description": "快速释义"}],
+            "meanings": [{"richDefinitions": [{"fragments": [{"text": "英文释义"}]}]}]
+        }]}]}))
+    });
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"bing\"]\n\n[bing]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+    sandbox.seed_ns("zh", "满", &entry("满", &["adj. full"]));
+    sandbox.seed_ns("zh", "行", &entry("行", &["v. walk"]));
+    sandbox.seed_ns("zh", "嫦娥", &entry("嫦娥", &["n. Chang'e"]));
+    sandbox.seed_ns("zh", "诺信", &entry("诺信", &["n. promise"]));
+
+    // 在线查得到的英文单词不按拼音解释
+    for word in ["man", "hang", "change"] {
+        let result = sandbox.query_json(&[word]);
+        assert_eq!(result["headword"], serde_json::Value::Null, "{}", word);
+        assert_eq!(result["translations"], json!(["n. 英文释义"]), "{}", word);
+    }
+
+    // 明确指定 --from pinyin 时直接按拼音查找
+    let result = sandbox.query_json(&["--from", "pinyin", "hang"]);
+    assert_eq!(result["headword"], "行");
+    assert_eq!(result["translations"], json!(["v. walk"]));
+
+    // 在线查不到时才退回到拼音
+    let result = sandbox.query_json(&["nuoxin"]);
+    assert_eq!(result["headword"], "诺信");
+    assert_eq!(result["translations"], json!(["n. promise"]));
+    assert_eq!(server.requests().len(), 4);
+}
+
+#[test]
+fn test_pinyin_tones_rank_candidates() {
+    let sandbox = chinese_sandbox();
+
+    let result = sandbox.query_json(&["shi4yan2"]);
+    assert_eq!(result["headword"], "誓言");
+    let others = result["suggestions"].as_array().unwrap();
+    assert_eq!(others.len(), 2);
+    assert!(others.contains(&json!("实验")));
+    assert!(others.contains(&json!("试验")));
+
+    let result = sandbox.query_json(&["shìyàn"]);
+    assert_eq!(result["headword"], "试验");
+
+    let output = sandbox.run(&["shiyan"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("shiyan → 实验"), "stdout: {}", stdout);
+    assert!(stdout.contains("其他候选"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_old_cache_table_gains_namespaces() {
+    let sandbox = Sandbox::new("providers = []\n");
+    std::fs::create_dir_all(sandbox.kd_dir()).unwrap();
+
+    // 引入命名空间之前的表结构：以 query 为主键，全文索引按 rowid 关联
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE cache (
+            query TEXT PRIMARY KEY,
+            data BLOB NOT NULL,
+            compressed_size INTEGER NOT NULL,
+            original_size INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE VIRTUAL TABLE cache_fts USING fts5(
+            query, translations, summary UNINDEXED,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+        CREATE TRIGGER cache_fts_delete AFTER DELETE ON cache BEGIN
+            DELETE FROM cache_fts WHERE rowid = old.rowid;
+        END;",
+    )
+    .unwrap();
+    for (rowid, word, meaning, segmented) in [
+        (7, "apple", "n. 苹果", "n.  苹  果 "),
+        (9, "电脑", "n. computer", "n. computer"),
+    ] {
+        let data = serde_json::to_vec(&entry(word, &[meaning])).unwrap();
+        let compressed = zstd::encode_all(&data[..], 0).unwrap();
+        conn.execute(
+            "INSERT INTO cache (rowid, query, data, compressed_size, original_size, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, 0, 0)",
+            rusqlite::params![rowid, word, compressed, compressed.len(), data.len()],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO cache_fts (rowid, query, translations, summary) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![rowid, word, segmented, meaning],
+        )
+        .unwrap();
+    }
+    drop(conn);
+
+    assert_eq!(sandbox.query_json(&["diannao"])["headword"], "电脑");
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    let mut stmt = conn
+        .prepare("SELECT rowid, ns, query FROM cache ORDER BY rowid")
+        .unwrap();
+    let rows: Vec<(i64, String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        [
+            (7, "en".to_string(), "apple".to_string()),
+            (9, "zh".to_string(), "电脑".to_string())
+        ]
+    );
+
+    // 全文索引仍然有效
+    let hits = sandbox.query_json(&["--search", "苹果"]);
+    assert_eq!(hits[0]["query"], "apple");
+}
@@ -0,0 +1,275 @@
+//! 集成测试公共工具：本地桩服务器与隔离的配置目录
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// 桩服务器收到的请求
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub method: String,
+    /// 路径（包含查询字符串）
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl StubRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn body_json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("request body is not JSON")
+    }
+}
+
+/// 桩服务器返回的响应
+#[derive(Debug, Clone)]
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl StubResponse {
+    pub fn json(value: serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    pub fn bytes(body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+type Handler = dyn Fn(&StubRequest) -> StubResponse + Send + Sync;
+
+/// 在本地随机端口上运行的 HTTP 桩服务器
+pub struct StubServer {
+    pub addr: SocketAddr,
+    requests: Arc<Mutex<Vec<StubRequest>>>,
+}
+
+impl StubServer {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&StubRequest) -> StubResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                thread::spawn(move || handle_connection(stream, &*handler, &recorded));
+            }
+        });
+
+        Self { addr, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn requests(&self) -> Vec<StubRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn handle_connection(stream: TcpStream, handler: &Handler, recorded: &Mutex<Vec<StubRequest>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = line.split_once(':') {
+            headers.push((k.trim().to_string(), v.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok();
+
+    let request = StubRequest {
+        method,
+        path,
+        headers,
+        body,
+    };
+    recorded.lock().unwrap().push(request.clone());
+
+    let response = handler(&request);
+    let mut stream = stream;
+    let mut head = format!("HTTP/1.1 {} Stub\r\n", response.status);
+    for (k, v) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    // 响应可自带 Content-Length（如模拟传输中断）
+    if !response
+        .headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+    {
+        head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+    }
+    head.push_str("Connection: close\r\n\r\n");
+    stream.write_all(head.as_bytes()).ok();
+    stream.write_all(&response.body).ok();
+    stream.flush().ok();
+}
+
+/// 基础测试配置：关闭分页与日志，避免干扰输出
+const BASE_CONFIG: &str = r#"
+paging = false
+clear_screen = false
+enable_emoji = false
+"#;
+
+const LOGGING_CONFIG: &str = r#"
+[logging]
+enable = false
+"#;
+
+/// 隔离的 kd 运行环境（独立的配置目录和数据库）
+pub struct Sandbox {
+    dir: tempfile::TempDir,
+}
+
+impl Sandbox {
+    /// 使用给定的配置内容创建运行环境（顶层键需写在各个表之前）
+    pub fn new(config: &str) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let sandbox = Self { dir };
+        std::fs::create_dir_all(sandbox.kd_dir()).unwrap();
+        let content = format!("{}{}\n{}", BASE_CONFIG, config, LOGGING_CONFIG);
+        std::fs::write(sandbox.kd_dir().join("config.toml"), content).unwrap();
+        sandbox
+    }
+
+    /// 配置目录（~/.config/kd）
+    pub fn kd_dir(&self) -> PathBuf {
+        self.dir.path().join("kd")
+    }
+
+    pub fn db_path(&self) -> PathBuf {
+        self.kd_dir().join("kd.db")
+    }
+
+    pub fn kd(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_kd"));
+        cmd.env("XDG_CONFIG_HOME", self.dir.path())
+            .env("NO_COLOR", "1")
+            .env_remove("RUST_LOG");
+        cmd
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.kd().args(args).output().unwrap()
+    }
+
+    /// 初始化数据库（首次运行 kd 时自动建表）
+    pub fn init_db(&self) {
+        if !self.db_path().exists() {
+            let output = self.run(&["--status"]);
+            assert!(output.status.success(), "kd --status failed");
+        }
+    }
+
+    /// 直接向缓存表写入一条英文词条（按旧格式：zstd 压缩的 JSON）
+    pub fn seed(&self, word: &str, result: &serde_json::Value) {
+        self.seed_ns("en", word, result);
+    }
+
+    /// 向指定命名空间（en 英文词条 / zh 中文词条）写入一条词条
+    pub fn seed_ns(&self, ns: &str, word: &str, result: &serde_json::Value) {
+        self.init_db();
+        let data = serde_json::to_vec(result).unwrap();
+        let compressed = zstd::encode_all(&data[..], 0).unwrap();
+        let conn = rusqlite::Connection::open(self.db_path()).unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO cache (ns, query, data, compressed_size, original_size, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, 0, 0)",
+            rusqlite::params![ns, word, compressed, compressed.len(), data.len()],
+        )
+        .unwrap();
+    }
+
+    /// 以 --json 方式查询并解析输出
+    pub fn query_json(&self, args: &[&str]) -> serde_json::Value {
+        let mut full = vec!["--json"];
+        full.extend_from_slice(args);
+        let output = self.run(&full);
+        assert!(
+            output.status.success(),
+            "kd failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice(&output.stdout).expect("kd did not print JSON")
+    }
+}
+
+/// 构造一条离线词典词条（QueryResult 的 JSON 形式）
+pub fn entry(word: &str, translations: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "query": word,
+        "found": true,
+        "is_long_text": false,
+        "pronunciation": null,
+        "pronunciation_us": null,
+        "pronunciation_uk": null,
+        "translations": translations,
+        "examples": [],
+        "collins_items": [],
+        "collins_rank": null,
+        "source": "OfflineDb",
+        "cached_at": null
+    })
+}
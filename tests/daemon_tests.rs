@@ -0,0 +1,100 @@
+//! 守护进程模式测试（Unix 套接字转发与回退）
+#![cfg(unix)]
+
+mod common;
+
+use common::{entry, Sandbox};
+use std::process::{Child, Stdio};
+use std::time::{Duration, Instant};
+
+struct Daemon(Child);
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+fn start_daemon(sandbox: &Sandbox) -> Daemon {
+    let child = sandbox
+        .kd()
+        .arg("--daemon")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let daemon = Daemon(child);
+
+    let socket = sandbox.kd_dir().join("kd.sock");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !socket.exists() {
+        assert!(Instant::now() < deadline, "daemon did not start");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    daemon
+}
+
+#[test]
+fn test_queries_are_forwarded_to_daemon() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+
+    let daemon = start_daemon(&sandbox);
+
+    // 第一次由守护进程从数据库读取，第二次命中守护进程的内存缓存
+    let first = sandbox.query_json(&["hello"]);
+    assert_eq!(first["source"], "OfflineDb");
+    let second = sandbox.query_json(&["hello"]);
+    assert_eq!(second["source"], "LocalCache");
+    assert_eq!(second["translations"][0], "int. 你好");
+
+    let status = sandbox.run(&["--status"]);
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("Daemon: running"), "status: {}", stdout);
+    assert!(stdout.contains("1 entries"), "status: {}", stdout);
+    assert!(stdout.contains("1 hits, 1 misses"), "status: {}", stdout);
+
+    drop(daemon);
+
+    // 守护进程退出后回退到进程内查询（残留的套接字文件不影响）
+    let fallback = sandbox.query_json(&["hello"]);
+    assert_eq!(fallback["source"], "OfflineDb");
+}
+
+#[test]
+fn test_daemon_errors_are_reported_to_client() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.init_db();
+    let _daemon = start_daemon(&sandbox);
+
+    let output = sandbox.run(&["--json", "missing"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No online provider enabled"));
+}
+
+#[test]
+fn test_socket_is_private_to_owner() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new("providers = []\n");
+    let _daemon = start_daemon(&sandbox);
+
+    let socket = sandbox.kd_dir().join("kd.sock");
+    let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    // 用于创建套接字的临时目录不会残留
+    assert!(!sandbox.kd_dir().join("kd.sock.new").exists());
+}
+
+#[test]
+fn test_second_daemon_refuses_to_start() {
+    let sandbox = Sandbox::new("providers = []\n");
+    let _daemon = start_daemon(&sandbox);
+
+    let output = sandbox.run(&["--daemon"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already running"));
+}
@@ -0,0 +1,128 @@
+//! zstd 字典压缩测试（kd cache train-dict 与无字典旧数据的兼容）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+const WORDS: usize = 400;
+
+/// 在一个事务中写入一批结构相近的离线词条
+fn seed_dictionary(sandbox: &Sandbox) {
+    sandbox.init_db();
+    let mut conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    let tx = conn.transaction().unwrap();
+    for i in 0..WORDS {
+        let word = format!("word{}", i);
+        let mut result = entry(&word, &[&format!("n. 第{}个词", i), "v. 示例释义"]);
+        result["pronunciation_us"] = json!(format!("/wɜːd{}/", i));
+        result["examples"] =
+            json!([[format!("This is word {}.", i), format!("这是第{}个词。", i)]]);
+        let data = serde_json::to_vec(&result).unwrap();
+        let compressed = zstd::encode_all(&data[..], 0).unwrap();
+        tx.execute(
+            "INSERT INTO cache (ns, query, data, compressed_size, original_size, created_at, updated_at)
+             VALUES ('en', ?1, ?2, ?3, ?4, 0, 0)",
+            rusqlite::params![word, compressed, compressed.len(), data.len()],
+        )
+        .unwrap();
+    }
+    tx.commit().unwrap();
+}
+
+fn compressed_size(sandbox: &Sandbox) -> i64 {
+    let output = sandbox.run(&["cache", "stats", "--json"]);
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    stats["compressed_size"].as_i64().unwrap()
+}
+
+fn train(sandbox: &Sandbox) -> String {
+    let output = sandbox.run(&["cache", "train-dict", "--size", "8KB"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_train_dict_recompresses_entries() {
+    let sandbox = Sandbox::new("providers = []\n");
+    seed_dictionary(&sandbox);
+    let before = compressed_size(&sandbox);
+
+    let stdout = train(&sandbox);
+
+    assert!(stdout.contains("Trained dictionary v1"), "{}", stdout);
+    assert!(
+        stdout.contains(&format!("Recompressed {} entries", WORDS)),
+        "{}",
+        stdout
+    );
+    assert!(compressed_size(&sandbox) < before / 2);
+
+    // 行数据改用字典压缩，不带字典无法解压
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    let data: Vec<u8> = conn
+        .query_row("SELECT data FROM cache WHERE query = 'word7'", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert!(zstd::decode_all(&data[..]).is_err());
+    let dictionaries: i64 = conn
+        .query_row("SELECT COUNT(*) FROM zstd_dicts", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(dictionaries, 1);
+
+    let result = sandbox.query_json(&["word7"]);
+    assert_eq!(result["translations"], json!(["n. 第7个词", "v. 示例释义"]));
+    assert_eq!(result["examples"][0][1], "这是第7个词。");
+    let output = sandbox.run(&["cache", "show", "word12"]);
+    let shown: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(shown[0]["data"]["pronunciation_us"], "/wɜːd12/");
+}
+
+#[test]
+fn test_rows_without_dictionary_still_decode() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "你好"})));
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"libretranslate\"]\n\n[libretranslate]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+    seed_dictionary(&sandbox);
+    train(&sandbox);
+
+    // 训练之后写入的无字典旧格式数据
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+    assert_eq!(
+        sandbox.query_json(&["apple"])["translations"],
+        json!(["n. 苹果"])
+    );
+
+    // 新查到的在线结果用字典压缩，重启后仍能读取
+    sandbox.query_json(&["hello"]);
+    let cached = sandbox.query_json(&["hello"]);
+    assert_eq!(cached["translations"], json!(["你好"]));
+    assert_eq!(server.requests().len(), 1);
+
+    // 再次训练生成新版本，旧版本字典压缩的数据一并迁移
+    let stdout = train(&sandbox);
+    assert!(stdout.contains("Trained dictionary v2"), "{}", stdout);
+    assert_eq!(
+        sandbox.query_json(&["word3"])["translations"][0],
+        "n. 第3个词"
+    );
+}
+
+#[test]
+fn test_train_dict_needs_entries() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    let output = sandbox.run(&["cache", "train-dict"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("could not train a dictionary"));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
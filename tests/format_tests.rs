@@ -0,0 +1,75 @@
+//! 存储格式测试（二进制编码与旧 JSON 行的按需改写）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+/// 解压某个词条的数据（未使用字典）
+fn stored_bytes(sandbox: &Sandbox, word: &str) -> (Vec<u8>, i64) {
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    let (data, updated_at): (Vec<u8>, i64) = conn
+        .query_row(
+            "SELECT data, updated_at FROM cache WHERE query = ?1",
+            [word],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    (zstd::decode_all(&data[..]).unwrap(), updated_at)
+}
+
+#[test]
+fn test_json_rows_are_rewritten_on_read() {
+    let sandbox = Sandbox::new("providers = []\n");
+    let mut result = entry("apple", &["n. 苹果"]);
+    result["examples"] = json!([["An apple a day.", "一天一个苹果。"]]);
+    sandbox.seed("apple", &result);
+    assert_eq!(stored_bytes(&sandbox, "apple").0[0], b'{');
+
+    let first = sandbox.query_json(&["apple"]);
+
+    let (bytes, updated_at) = stored_bytes(&sandbox, "apple");
+    assert_eq!(bytes[0], 1, "row was not rewritten in the binary format");
+    // 内容未变，更新时间保持不变
+    assert_eq!(updated_at, 0);
+
+    let second = sandbox.query_json(&["apple"]);
+    assert_eq!(second, first);
+    assert_eq!(second["examples"][0][1], "一天一个苹果。");
+}
+
+#[test]
+fn test_new_rows_use_binary_format() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "你好"})));
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"libretranslate\"]\n\n[libretranslate]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+
+    sandbox.query_json(&["hello"]);
+
+    let (bytes, _) = stored_bytes(&sandbox, "hello");
+    assert_eq!(bytes[0], 1);
+    let cached = sandbox.query_json(&["hello"]);
+    assert_eq!(cached["translations"], json!(["你好"]));
+    assert_eq!(cached["source"]["Online"], "LibreTranslate");
+}
+
+#[test]
+fn test_unknown_format_version_is_reported() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.init_db();
+    let data = zstd::encode_all(&[9u8, 1, 2, 3][..], 0).unwrap();
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    conn.execute(
+        "INSERT INTO cache (query, data, compressed_size, original_size, created_at, updated_at)
+         VALUES ('apple', ?1, ?2, 4, 0, 0)",
+        rusqlite::params![data, data.len()],
+    )
+    .unwrap();
+
+    let output = sandbox.run(&["--json", "apple"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown result format version 9"));
+}
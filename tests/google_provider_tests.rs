@@ -0,0 +1,110 @@
+//! Google 翻译查询源测试（长句翻译，使用本地桩服务器）
+
+mod common;
+
+use common::{Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+fn google_sandbox(google: &StubServer, extra: &str) -> Sandbox {
+    Sandbox::new(&format!(
+        r#"long_text_providers = ["google", "bing"]
+
+[google]
+endpoint = "{}"
+api_key = "test-key"
+{}"#,
+        google.url(),
+        extra
+    ))
+}
+
+#[test]
+fn test_google_translates_long_text() {
+    let server = StubServer::start(|_| {
+        StubResponse::json(json!({
+            "data": {"translations": [{
+                "translatedText": "敏捷的棕色狐狸",
+                "detectedSourceLanguage": "en"
+            }]}
+        }))
+    });
+    let sandbox = google_sandbox(&server, "");
+
+    let result = sandbox.query_json(&["--nocache", "-t", "The quick brown fox"]);
+
+    assert_eq!(result["found"], true);
+    assert_eq!(result["is_long_text"], true);
+    assert_eq!(result["translations"], json!(["敏捷的棕色狐狸"]));
+    assert_eq!(result["source"], json!({"Online": "Google"}));
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/language/translate/v2?key=test-key");
+    let body = requests[0].body_json();
+    assert_eq!(body["q"], "The quick brown fox");
+    assert_eq!(body["source"], "en");
+    assert_eq!(body["target"], "zh-CN");
+}
+
+#[test]
+fn test_google_targets_english_for_chinese_text() {
+    let server = StubServer::start(|_| {
+        StubResponse::json(json!({
+            "data": {"translations": [{"translatedText": "Good morning"}]}
+        }))
+    });
+    let sandbox = google_sandbox(&server, "");
+
+    sandbox.query_json(&["--nocache", "-t", "早上好"]);
+
+    let body = server.requests()[0].body_json();
+    assert_eq!(body["source"], "zh-CN");
+    assert_eq!(body["target"], "en");
+}
+
+#[test]
+fn test_google_error_falls_back_to_next_provider() {
+    let google = StubServer::start(|_| StubResponse {
+        status: 403,
+        ..StubResponse::json(json!({
+            "error": {"code": 403, "message": "Daily Limit Exceeded"}
+        }))
+    });
+    let bing = StubServer::start(|_| {
+        StubResponse::json(json!({"value": [{"meaningGroups": [{
+            "partsOfSpeech": [{"name": "", "description": "快速释义"}],
+            "meanings": [{"richDefinitions": [{"fragments": [{"text": "敏捷的狐狸"}]}]}]
+        }]}]}))
+    });
+    let sandbox = google_sandbox(
+        &google,
+        &format!("\n[bing]\nendpoint = \"{}\"\n", bing.url()),
+    );
+
+    let result = sandbox.query_json(&["--nocache", "-t", "quick fox"]);
+
+    assert_eq!(google.requests().len(), 1);
+    assert_eq!(result["found"], true);
+    assert_eq!(result["is_long_text"], true);
+    assert_eq!(result["source"], json!({"Online": "Bing"}));
+}
+
+#[test]
+fn test_google_without_api_key_is_skipped() {
+    let server = StubServer::start(|_| StubResponse::status(500));
+    let sandbox = Sandbox::new(&format!(
+        r#"long_text_providers = ["google"]
+
+[google]
+endpoint = "{}"
+"#,
+        server.url()
+    ));
+
+    let output = sandbox.run(&["--json", "--nocache", "-t", "some text"]);
+
+    assert!(!output.status.success());
+    assert!(server.requests().is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Google API Key not configured"));
+}
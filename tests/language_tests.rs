@@ -0,0 +1,211 @@
+//! 语言识别测试（detected_lang、english_only 模式与查询源的 from/to 参数）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+fn libre_sandbox(server: &StubServer) -> Sandbox {
+    Sandbox::new(&format!(
+        "providers = [\"libretranslate\"]\n\n[libretranslate]\nendpoint = \"{}\"\n",
+        server.url()
+    ))
+}
+
+#[test]
+fn test_detects_query_language() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "译文"})));
+    let sandbox = libre_sandbox(&server);
+
+    for (query, lang) in [
+        ("naïve", "en"),
+        ("don't", "en"),
+        ("e-mail,", "en"),
+        ("Where is the train station", "en"),
+        ("Où est la gare, s'il vous plaît ?", "fr"),
+        ("Wo ist der Bahnhof und wie komme ich dahin", "de"),
+        ("¿Dónde está la estación?", "es"),
+        ("こんにちは", "ja"),
+        ("안녕하세요", "ko"),
+        ("привет", "ru"),
+        ("你好", "zh"),
+    ] {
+        let result = sandbox.query_json(&["--nocache", query]);
+        assert_eq!(result["detected_lang"], lang, "{}", query);
+    }
+}
+
+#[test]
+fn test_query_without_letters_has_no_language() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("404", &entry("404", &["未找到"]));
+
+    let result = sandbox.query_json(&["404"]);
+
+    assert_eq!(result["found"], true);
+    assert_eq!(result["detected_lang"], json!(null));
+}
+
+#[test]
+fn test_provider_receives_detected_languages() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "译文"})));
+    let sandbox = libre_sandbox(&server);
+
+    sandbox.query_json(&["--nocache", "Bonjour, comment allez-vous aujourd'hui ?"]);
+    sandbox.query_json(&["--nocache", "早上好"]);
+    sandbox.query_json(&["--nocache", "!!!"]);
+
+    let bodies: Vec<_> = server.requests().iter().map(|r| r.body_json()).collect();
+    assert_eq!(
+        (&bodies[0]["source"], &bodies[0]["target"]),
+        (&json!("fr"), &json!("zh"))
+    );
+    assert_eq!(
+        (&bodies[1]["source"], &bodies[1]["target"]),
+        (&json!("zh"), &json!("en"))
+    );
+    // 无法识别时交给查询源自动检测
+    assert_eq!(
+        (&bodies[2]["source"], &bodies[2]["target"]),
+        (&json!("auto"), &json!("zh"))
+    );
+}
+
+#[test]
+fn test_english_only_accepts_punctuated_english() {
+    let sandbox = Sandbox::new("english_only = true\nproviders = []\n");
+    for word in ["naïve", "don't", "e-mail,"] {
+        let mut result = entry(word, &["adj. 英文释义", "中文释义"]);
+        result["pronunciation_us"] = json!("/test/");
+        sandbox.seed(word, &result);
+    }
+
+    for word in ["naïve", "don't", "e-mail,"] {
+        let output = sandbox.run(&[word]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{}", word);
+        assert!(stdout.contains("US /test/"), "{}: {}", word, stdout);
+        assert!(!stdout.contains("中文释义"), "{}: {}", word, stdout);
+    }
+}
+
+#[test]
+fn test_target_language_option() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "こんにちは"})));
+    let sandbox = libre_sandbox(&server);
+
+    let result = sandbox.query_json(&["--nocache", "--to", "JP", "hello"]);
+    assert_eq!(result["lang_pair"], json!({"from": "en", "to": "ja"}));
+    sandbox.query_json(&["--nocache", "--from", "de", "--to", "zh-Hant", "Hallo"]);
+
+    let bodies: Vec<_> = server.requests().iter().map(|r| r.body_json()).collect();
+    assert_eq!(
+        (&bodies[0]["source"], &bodies[0]["target"]),
+        (&json!("en"), &json!("ja"))
+    );
+    assert_eq!(
+        (&bodies[1]["source"], &bodies[1]["target"]),
+        (&json!("de"), &json!("zt"))
+    );
+}
+
+#[test]
+fn test_config_default_languages() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "Hallo"})));
+    let sandbox = Sandbox::new(&format!(
+        "target_lang = \"de\"\nproviders = [\"libretranslate\"]\n\n[libretranslate]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+
+    sandbox.query_json(&["--nocache", "hello"]);
+    // 命令行参数优先于配置
+    sandbox.query_json(&["--nocache", "--to", "fr", "hello"]);
+
+    let bodies: Vec<_> = server.requests().iter().map(|r| r.body_json()).collect();
+    assert_eq!(bodies[0]["target"], "de");
+    assert_eq!(bodies[1]["target"], "fr");
+}
+
+#[test]
+fn test_language_pair_is_part_of_cache_key() {
+    let server = StubServer::start(|request| {
+        let text = match request.body_json()["target"].as_str() {
+            Some("ja") => "りんご",
+            _ => "Apfel",
+        };
+        StubResponse::json(json!({"translatedText": text}))
+    });
+    let sandbox = libre_sandbox(&server);
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    let ja = sandbox.query_json(&["--to", "ja", "apple"]);
+    let de = sandbox.query_json(&["--to", "de", "apple"]);
+    let cached = sandbox.query_json(&["--to", "ja", "apple"]);
+    let zh = sandbox.query_json(&["apple"]);
+
+    assert_eq!(ja["translations"], json!(["りんご"]));
+    assert_eq!(de["translations"], json!(["Apfel"]));
+    assert_eq!(cached["translations"], json!(["りんご"]));
+    assert_eq!(zh["translations"], json!(["n. 苹果"]));
+    assert_eq!(zh["source"], "OfflineDb");
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn test_header_shows_language_pair() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "りんご"})));
+    let sandbox = libre_sandbox(&server);
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    let output = sandbox.run(&["--to", "ja", "apple"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.lines().next().unwrap().contains("en → ja"),
+        "{}",
+        stdout
+    );
+
+    // 常规的英汉查询同样显示语言对
+    let output = sandbox.run(&["apple"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.lines().next().unwrap().contains("en → zh"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_unknown_language_is_rejected() {
+    let sandbox = Sandbox::new("providers = []\n");
+
+    let output = sandbox.run(&["--to", "klingon!", "hello"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown language: klingon!"));
+}
+
+#[test]
+fn test_borrowed_phrases_stay_in_english_dictionary() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "译文"})));
+    let sandbox = libre_sandbox(&server);
+    sandbox.seed(
+        "de facto standard",
+        &entry("de facto standard", &["事实标准"]),
+    );
+
+    // 看起来像法语但不够确定：仍查英文词库
+    let result = sandbox.query_json(&["de facto standard"]);
+    assert_eq!(result["found"], true);
+    assert_eq!(result["translations"], json!(["事实标准"]));
+    assert_eq!(result["lang_pair"], json!({"from": "auto", "to": "zh"}));
+
+    // 在线查询时交给查询源自动检测
+    let result = sandbox.query_json(&["--nocache", "coup de grace"]);
+    assert_eq!(result["lang_pair"], json!({"from": "auto", "to": "zh"}));
+    assert_eq!(server.requests()[0].body_json()["source"], "auto");
+
+    // 明确指定语言时照常使用
+    let result = sandbox.query_json(&["--nocache", "--from", "fr", "coup de grace"]);
+    assert_eq!(result["lang_pair"], json!({"from": "fr", "to": "zh"}));
+}
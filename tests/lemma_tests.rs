@@ -0,0 +1,136 @@
+//! 词形还原测试（屈折变化形式查到原形词条）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::Stdio;
+
+fn lemma_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new("providers = []\n");
+    for (word, meaning) in [
+        ("run", "v. 跑"),
+        ("go", "v. 去"),
+        ("analysis", "n. 分析"),
+        ("study", "v. 学习"),
+        ("make", "v. 制作"),
+        ("wolf", "n. 狼"),
+        ("big", "adj. 大的"),
+    ] {
+        sandbox.seed(word, &entry(word, &[meaning]));
+    }
+    sandbox
+}
+
+#[test]
+fn test_inflected_forms_resolve_to_headword() {
+    let sandbox = lemma_sandbox();
+
+    for (form, lemma) in [
+        ("running", "run"),
+        ("went", "go"),
+        ("analyses", "analysis"),
+        ("studied", "study"),
+        ("making", "make"),
+        ("wolves", "wolf"),
+        ("biggest", "big"),
+        ("Runs", "run"),
+    ] {
+        let result = sandbox.query_json(&[form]);
+        assert_eq!(result["found"], true, "{}", form);
+        assert_eq!(result["query"], form);
+        assert_eq!(result["headword"], lemma, "{}", form);
+    }
+}
+
+#[test]
+fn test_exact_match_has_no_headword() {
+    let sandbox = lemma_sandbox();
+
+    let result = sandbox.query_json(&["run"]);
+    assert_eq!(result["query"], "run");
+    assert_eq!(result["headword"], Value::Null);
+}
+
+#[test]
+fn test_output_notes_resolution() {
+    let sandbox = lemma_sandbox();
+
+    let output = sandbox.run(&["running"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("running → run"), "stdout: {}", stdout);
+    assert!(stdout.contains("v. 跑"));
+}
+
+/// 词库里有 but、let 等原形，但没有 butter、letter 本身
+const LOOKALIKES: [(&str, &str); 8] = [
+    ("butter", "but"),
+    ("letter", "let"),
+    ("only", "on"),
+    ("early", "ear"),
+    ("thing", "the"),
+    ("news", "new"),
+    ("seed", "see"),
+    ("number", "numb"),
+];
+
+fn lookalike_sandbox(config: &str) -> Sandbox {
+    let sandbox = Sandbox::new(config);
+    for (_, stem) in LOOKALIKES {
+        sandbox.seed(stem, &entry(stem, &["x. 无关词条"]));
+    }
+    sandbox
+}
+
+#[test]
+fn test_online_result_wins_over_lemma_guess() {
+    let server = StubServer::start(|_| {
+        StubResponse::json(json!({"value": [{"meaningGroups": [{
+            "partsOfSpeech": [{"name": "n.", "description": "快速释义"}],
+            "meanings": [{"richDefinitions": [{"fragments": [{"text": "在线释义"}]}]}]
+        }]}]}))
+    });
+    let sandbox = lookalike_sandbox(&format!(
+        "providers = [\"bing\"]\n\n[bing]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+
+    for (word, _) in LOOKALIKES {
+        let result = sandbox.query_json(&[word]);
+        assert_eq!(result["found"], true, "{}", word);
+        assert_eq!(result["headword"], Value::Null, "{}", word);
+        assert_eq!(result["translations"], json!(["n. 在线释义"]), "{}", word);
+    }
+}
+
+#[test]
+fn test_lemma_guess_is_not_cached() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"value": []})));
+    let sandbox = lookalike_sandbox(&format!(
+        "providers = [\"bing\"]\n\n[bing]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+
+    // 在线查不到时才退回到词形还原，且结果不进入内存缓存，下次仍会在线查询
+    let output = sandbox.run(&["butter"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("butter → but"));
+
+    let mut child = sandbox
+        .kd()
+        .args(["-i", "--json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"butter\nbutter\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(server.requests().len(), 3);
+}
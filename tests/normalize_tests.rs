@@ -0,0 +1,151 @@
+//! 查询规范化测试（大小写、空白、全角、NFKC 及旧数据迁移）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+fn cache_keys(sandbox: &Sandbox) -> Vec<String> {
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    let mut stmt = conn
+        .prepare("SELECT query FROM cache ORDER BY query")
+        .unwrap();
+    let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+    rows.collect::<Result<_, _>>().unwrap()
+}
+
+#[test]
+fn test_equivalent_queries_share_one_entry() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+    sandbox.seed("ice cream", &entry("ice cream", &["n. 冰淇淋"]));
+
+    for query in ["Apple", "APPLE", "apple ", "ＡＰＰＬＥ"] {
+        let result = sandbox.query_json(&[query]);
+        assert_eq!(result["found"], true, "{:?}", query);
+        // 显示时保留用户输入
+        assert_eq!(result["query"], query.trim(), "{:?}", query);
+    }
+
+    let result = sandbox.query_json(&["Ice", " cream"]);
+    assert_eq!(result["found"], true);
+    let result = sandbox.query_json(&["ice\u{3000}cream"]);
+    assert_eq!(result["found"], true);
+}
+
+#[test]
+fn test_online_results_stored_under_normalized_key() {
+    let server = StubServer::start(|_| {
+        StubResponse::json(json!({"value": [{"meaningGroups": [{
+            "partsOfSpeech": [{"name": "adj.", "description": "快速释义"}],
+            "meanings": [{"richDefinitions": [{"fragments": [{"text": "慢的"}]}]}]
+        }]}]}))
+    });
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"bing\"]\n\n[bing]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+
+    assert_eq!(sandbox.query_json(&["SLOW"])["found"], true);
+    assert_eq!(sandbox.query_json(&["Slow"])["found"], true);
+
+    assert_eq!(cache_keys(&sandbox), ["slow"]);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].path.contains("q=slow"));
+}
+
+#[test]
+fn test_existing_rows_are_migrated() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+    sandbox.seed("Apple", &entry("Apple", &["n. 苹果公司"]));
+    sandbox.seed("Hello  World", &entry("Hello  World", &["你好，世界"]));
+    sandbox.seed("ＡＢＣ", &entry("ＡＢＣ", &["字母表"]));
+    let mut sentence = entry("Good  Morning", &["早上好"]);
+    sentence["is_long_text"] = json!(true);
+    sandbox.seed("Good  Morning", &sentence);
+
+    // 模拟规范化之前写入的旧数据库
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    conn.execute("DELETE FROM meta", []).unwrap();
+    drop(conn);
+
+    let result = sandbox.query_json(&["hello world"]);
+    assert_eq!(result["found"], true);
+    // 长句保留大小写，只规范空白
+    // 与已有词条冲突的离线词条保留原来的键
+    assert_eq!(
+        cache_keys(&sandbox),
+        ["Apple", "Good Morning", "abc", "apple", "hello world"]
+    );
+    let result = sandbox.query_json(&["apple"]);
+    assert_eq!(result["translations"], json!(["n. 苹果"]));
+
+    // 迁移后的词条仍可被搜索到
+    let hits = sandbox.query_json(&["--search", "hello"]);
+    assert_eq!(hits[0]["query"], "hello world");
+}
+
+#[test]
+fn test_case_variant_headwords_survive_migration() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("us", &entry("us", &["pron. 我们"]));
+    sandbox.seed("US", &entry("US", &["abbr. 美国"]));
+    sandbox.seed("may", &entry("may", &["v. 可能"]));
+    sandbox.seed("May", &entry("May", &["n. 五月"]));
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+    sandbox.seed("Hello", &entry("Hello", &["在线结果"]));
+
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    conn.execute("UPDATE cache SET source = 'bing' WHERE query = 'Hello'", [])
+        .unwrap();
+    conn.execute("DELETE FROM meta", []).unwrap();
+    drop(conn);
+
+    sandbox.run(&["--status"]);
+
+    // 离线词条都保留，与之冲突的在线结果被丢弃
+    assert_eq!(cache_keys(&sandbox), ["May", "US", "hello", "may", "us"]);
+
+    // 按输入的大小写优先查到对应的词条，其他写法仍按小写查询
+    let cases = [
+        ("US", "abbr. 美国"),
+        ("us", "pron. 我们"),
+        ("Us", "pron. 我们"),
+        ("May", "n. 五月"),
+        ("may", "v. 可能"),
+        ("Hello", "int. 你好"),
+    ];
+    for (query, translation) in cases {
+        let result = sandbox.query_json(&[query]);
+        assert_eq!(result["translations"], json!([translation]), "{}", query);
+    }
+}
+
+#[test]
+fn test_case_is_only_folded_for_english() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "莫斯科"})));
+    let sandbox = Sandbox::new(&format!(
+        "source_lang = \"ru\"\nproviders = [\"libretranslate\"]\n\n[libretranslate]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+
+    assert_eq!(sandbox.query_json(&["Москва"])["found"], true);
+
+    // 非英文查询保留大小写
+    assert_eq!(cache_keys(&sandbox), ["Москва"]);
+    assert_eq!(server.requests()[0].body_json()["q"], "Москва");
+}
+
+#[test]
+fn test_case_folding_can_be_disabled() {
+    let sandbox = Sandbox::new("providers = []\n\n[normalization]\nfold_case = false\n");
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    assert_eq!(sandbox.query_json(&["apple "])["found"], true);
+    // 不区分大小写时查不到，只作为拼写建议给出
+    let result = sandbox.query_json(&["APPLE"]);
+    assert_eq!(result["found"], false);
+    assert_eq!(result["suggestions"], json!(["apple"]));
+}
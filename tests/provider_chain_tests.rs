@@ -0,0 +1,95 @@
+//! 在线查询源链测试：按配置顺序查询、跳过未配置的查询源、出错时换用下一个（使用本地桩服务器）
+
+mod common;
+
+use common::{Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+fn bing_server() -> StubServer {
+    StubServer::start(|_| {
+        StubResponse::json(json!({"value": [{"meaningGroups": [{
+            "partsOfSpeech": [{"name": "int.", "description": "快速释义"}],
+            "meanings": [{"richDefinitions": [{"fragments": [{"text": "你好"}]}]}]
+        }]}]}))
+    })
+}
+
+fn libretranslate_server() -> StubServer {
+    StubServer::start(|_| StubResponse::json(json!({"translatedText": "哈喽"})))
+}
+
+/// 以 providers 列表和两个桩服务器为查询源的运行环境
+fn chain_sandbox(providers: &str, bing: &StubServer, libretranslate: &StubServer) -> Sandbox {
+    Sandbox::new(&format!(
+        r#"providers = {}
+
+[bing]
+endpoint = "{}"
+
+[libretranslate]
+endpoint = "{}"
+"#,
+        providers,
+        bing.url(),
+        libretranslate.url()
+    ))
+}
+
+#[test]
+fn test_providers_are_tried_in_configured_order() {
+    let bing = bing_server();
+    let libretranslate = libretranslate_server();
+
+    let sandbox = chain_sandbox(r#"["libretranslate", "bing"]"#, &bing, &libretranslate);
+    let result = sandbox.query_json(&["--nocache", "hello"]);
+    assert_eq!(result["source"], json!({"Online": "LibreTranslate"}));
+    assert!(bing.requests().is_empty());
+
+    let sandbox = chain_sandbox(r#"["bing", "libretranslate"]"#, &bing, &libretranslate);
+    let result = sandbox.query_json(&["--nocache", "hello"]);
+    assert_eq!(result["source"], json!({"Online": "Bing"}));
+    assert_eq!(libretranslate.requests().len(), 1);
+}
+
+#[test]
+fn test_unconfigured_provider_is_skipped() {
+    let bing = bing_server();
+    let libretranslate = libretranslate_server();
+    // 没有配置 api_id / api_key 的有道被跳过
+    let sandbox = chain_sandbox(r#"["youdao", "bing"]"#, &bing, &libretranslate);
+
+    let output = sandbox.run(&["--json", "--nocache", "hello"]);
+
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["source"], json!({"Online": "Bing"}));
+    assert_eq!(result["translations"], json!(["int. 你好"]));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("WARN"));
+}
+
+#[test]
+fn test_failing_provider_falls_back_to_next() {
+    let bing = bing_server();
+    let broken = StubServer::start(|_| StubResponse::status(500));
+    let sandbox = chain_sandbox(r#"["libretranslate", "bing"]"#, &bing, &broken);
+
+    let result = sandbox.query_json(&["--nocache", "hello"]);
+
+    assert_eq!(result["found"], true);
+    assert_eq!(result["source"], json!({"Online": "Bing"}));
+    assert_eq!(broken.requests().len(), 1);
+    assert_eq!(bing.requests().len(), 1);
+}
+
+#[test]
+fn test_errors_of_every_provider_are_reported() {
+    let broken = StubServer::start(|_| StubResponse::status(500));
+    let sandbox = chain_sandbox(r#"["libretranslate", "bing"]"#, &broken, &broken);
+
+    let output = sandbox.run(&["--nocache", "hello"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("All providers failed"), "{}", stderr);
+    assert!(stderr.contains("LibreTranslate"), "{}", stderr);
+    assert!(stderr.contains("Bing"), "{}", stderr);
+}
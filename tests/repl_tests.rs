@@ -0,0 +1,115 @@
+//! 交互模式测试（通过标准输入驱动）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+use std::io::Write;
+use std::process::Stdio;
+
+fn run_repl(sandbox: &Sandbox, input: &str) -> (String, String) {
+    let mut child = sandbox
+        .kd()
+        .arg("-i")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn test_repl_reuses_state_between_lookups() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+
+    let (stdout, _) = run_repl(&sandbox, "hello\nhello\n:q\n");
+
+    // 同一会话中第二次查询命中内存缓存
+    assert!(stdout.contains("[离线]"), "stdout: {}", stdout);
+    assert!(stdout.contains("[缓存]"), "stdout: {}", stdout);
+    assert!(stdout.contains("int. 你好"));
+}
+
+#[test]
+fn test_repl_commands_toggle_settings() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+
+    let (stdout, stderr) = run_repl(
+        &sandbox,
+        ":json\nhello\n:en\n:nocache\n:theme wudao\n:theme bogus\n:theme\n:bogus\n",
+    );
+
+    assert!(stdout.contains("json: on"));
+    assert!(stdout.contains("\"source\": \"OfflineDb\""));
+    assert!(stdout.contains("english_only: on"));
+    assert!(stdout.contains("nocache: on"));
+    assert!(stdout.contains("theme: wudao"));
+    // 未知主题被拒绝，保留当前主题
+    assert!(stderr.contains("Unknown theme bogus, available: temp, wudao, canvas"));
+    assert!(!stdout.contains("theme: bogus"));
+    assert_eq!(stdout.matches("theme: wudao").count(), 2);
+    assert!(stderr.contains("Unknown command :bogus"));
+}
+
+#[test]
+fn test_repl_persists_input_history() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+
+    run_repl(&sandbox, "hello\n:json\n");
+
+    let history = std::fs::read_to_string(sandbox.kd_dir().join("repl_history")).unwrap();
+    assert!(history.contains("hello"));
+    assert!(history.contains(":json"));
+}
+
+#[test]
+fn test_repl_picks_suggestion_by_number() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    let (stdout, _) = run_repl(&sandbox, "appel\n1\n");
+
+    assert!(stdout.contains("1. apple"), "stdout: {}", stdout);
+    assert!(stdout.contains("n. 苹果"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_repl_suggests_words_stored_during_session() {
+    let server = StubServer::start(|request| {
+        if request.path.contains("q=zebra") {
+            StubResponse::json(json!({"value": [{"meaningGroups": [{
+                "partsOfSpeech": [{"name": "n.", "description": "快速释义"}],
+                "meanings": [{"richDefinitions": [{"fragments": [{"text": "斑马"}]}]}]
+            }]}]}))
+        } else {
+            StubResponse::json(json!({"value": []}))
+        }
+    });
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"bing\"]\n\n[bing]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    // 第一次未命中时建立拼写索引，之后在线查到的词也能作为建议
+    let (stdout, _) = run_repl(&sandbox, "appel\nzebra\nzebar\n");
+
+    assert!(stdout.contains("1. apple"), "stdout: {}", stdout);
+    assert!(stdout.contains("斑马"), "stdout: {}", stdout);
+    assert!(stdout.contains("1. zebra"), "stdout: {}", stdout);
+}
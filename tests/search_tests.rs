@@ -0,0 +1,131 @@
+//! 离线词库全文搜索测试（前缀、通配符、中文释义反查）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+fn search_sandbox() -> Sandbox {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("abandon", &entry("abandon", &["v. 放弃；遗弃", "n. 放任"]));
+    sandbox.seed(
+        "abandonment",
+        &entry("abandonment", &["n. 放弃；抛弃；放纵"]),
+    );
+    sandbox.seed(
+        "desert",
+        &entry("desert", &["n. 沙漠", "v. 抛弃；遗弃；离开；擅离职守"]),
+    );
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+    sandbox
+}
+
+fn search(sandbox: &Sandbox, pattern: &str) -> Vec<String> {
+    let hits = sandbox.query_json(&["--search", pattern]);
+    hits.as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["query"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_reverse_lookup_by_chinese_meaning() {
+    let sandbox = search_sandbox();
+
+    let hits = search(&sandbox, "遗弃");
+    assert_eq!(hits, ["abandon", "desert"]);
+
+    // 单个汉字也能命中释义中间的部分
+    let hits = search(&sandbox, "苹");
+    assert_eq!(hits, ["apple"]);
+}
+
+#[test]
+fn test_prefix_search_ranks_exact_headword_first() {
+    let sandbox = search_sandbox();
+
+    let hits = search(&sandbox, "aband");
+    assert_eq!(hits.len(), 2);
+    assert!(hits.contains(&"abandon".to_string()));
+    assert!(hits.contains(&"abandonment".to_string()));
+
+    let hits = search(&sandbox, "abandon");
+    assert_eq!(hits[0], "abandon");
+}
+
+#[test]
+fn test_wildcard_search() {
+    let sandbox = search_sandbox();
+
+    assert_eq!(search(&sandbox, "a*"), ["apple", "abandon", "abandonment"]);
+    assert_eq!(search(&sandbox, "des?rt"), ["desert"]);
+    assert!(search(&sandbox, "z*").is_empty());
+}
+
+#[test]
+fn test_search_output_and_limit() {
+    let sandbox = search_sandbox();
+
+    let output = sandbox.run(&["--search", "遗弃"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1."));
+    assert!(stdout.contains("abandon"));
+    assert!(stdout.contains("v. 放弃；遗弃"));
+
+    let hits = sandbox.query_json(&["--search", "a*", "--limit", "1"]);
+    assert_eq!(hits.as_array().unwrap().len(), 1);
+
+    let output = sandbox.run(&["--search", "nothing"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No entries match"));
+}
+
+#[test]
+fn test_online_results_are_searchable() {
+    let server = StubServer::start(|_| {
+        StubResponse::json(json!({"value": [{"meaningGroups": [{
+            "partsOfSpeech": [{"name": "adj.", "description": "快速释义"}],
+            "meanings": [{"richDefinitions": [{"fragments": [{"text": "慢的"}]}]}]
+        }]}]}))
+    });
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"bing\"]\n\n[bing]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+
+    assert!(search(&sandbox, "慢").is_empty());
+    assert_eq!(sandbox.query_json(&["slow"])["found"], true);
+    assert_eq!(search(&sandbox, "慢"), ["slow"]);
+    assert_eq!(search(&sandbox, "apple"), ["apple"]);
+}
+
+#[test]
+fn test_outdated_index_with_summary_column_is_rebuilt() {
+    let sandbox = search_sandbox();
+    // 模拟旧版本建立的索引：带 summary 列，且已标记为完整
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    conn.execute_batch(
+        "DROP TABLE cache_fts;
+         CREATE VIRTUAL TABLE cache_fts USING fts5(query, translations, summary UNINDEXED);
+         INSERT INTO cache_fts (rowid, query, translations, summary)
+             SELECT rowid, query, 'stale', 'stale' FROM cache;
+         INSERT OR REPLACE INTO meta (key, value) VALUES ('search_index', '0');",
+    )
+    .unwrap();
+    drop(conn);
+
+    let hits = sandbox.query_json(&["--search", "遗弃"]);
+    let hits = hits.as_array().unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0]["summary"], "v. 放弃；遗弃; n. 放任");
+
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    let summary_column: bool = conn
+        .prepare("SELECT 1 FROM pragma_table_info('cache_fts') WHERE name = 'summary'")
+        .unwrap()
+        .exists([])
+        .unwrap();
+    assert!(!summary_column);
+}
@@ -0,0 +1,222 @@
+//! 自建翻译服务测试：LibreTranslate 与通用 HTTP JSON 模板（使用本地桩服务器）
+
+mod common;
+
+use common::{Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+#[test]
+fn test_libretranslate_translates_query() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"translatedText": "你好，世界"})));
+    let sandbox = Sandbox::new(&format!(
+        r#"providers = ["libretranslate"]
+
+[libretranslate]
+endpoint = "{}/"
+api_key = "secret"
+"#,
+        server.url()
+    ));
+
+    let result = sandbox.query_json(&["--nocache", "hello world"]);
+
+    assert_eq!(result["found"], true);
+    assert_eq!(result["translations"], json!(["你好，世界"]));
+    assert_eq!(result["source"], json!({"Online": "LibreTranslate"}));
+
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/translate");
+    let body = requests[0].body_json();
+    assert_eq!(body["q"], "hello world");
+    assert_eq!(body["source"], "en");
+    assert_eq!(body["target"], "zh");
+    assert_eq!(body["api_key"], "secret");
+}
+
+#[test]
+fn test_libretranslate_error_message() {
+    let server = StubServer::start(|_| StubResponse {
+        status: 400,
+        ..StubResponse::json(json!({"error": "Invalid API key"}))
+    });
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"libretranslate\"]\n\n[libretranslate]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+
+    let output = sandbox.run(&["--json", "--nocache", "hello"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid API key"));
+}
+
+#[test]
+fn test_libretranslate_reports_status_for_non_json_error() {
+    let server = StubServer::start(|_| StubResponse {
+        status: 502,
+        ..StubResponse::bytes(b"<html>Bad Gateway</html>".to_vec())
+    });
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"libretranslate\"]\n\n[libretranslate]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+
+    let output = sandbox.run(&["--json", "--nocache", "hello"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("HTTP 502"), "unexpected stderr: {}", stderr);
+}
+
+#[test]
+fn test_http_template_maps_fields() {
+    let server = StubServer::start(|_| {
+        StubResponse::json(json!({
+            "data": {
+                "phonetic": "ˈæpl",
+                "senses": [{"text": "n. 苹果"}, {"text": "n. 苹果树"}],
+                "samples": [
+                    {"en": "An apple a day.", "zh": "一天一个苹果。"},
+                    {"en": "Apple pie.", "zh": "苹果派。"}
+                ]
+            }
+        }))
+    });
+    let sandbox = Sandbox::new(&format!(
+        r#"providers = ["corp-mt"]
+
+[[http_templates]]
+name = "corp-mt"
+url = "{}/v1/translate?q={{{{query}}}}&to={{{{to}}}}"
+body = '{{"text": "{{{{query}}}}"}}'
+
+[http_templates.headers]
+Authorization = "Bearer corp-token"
+
+[http_templates.fields]
+translations = "$.data.senses[*].text"
+pronunciation = "$.data.phonetic"
+examples_original = "$.data.samples[*].en"
+examples_translation = "$.data.samples[*].zh"
+"#,
+        server.url()
+    ));
+
+    let result = sandbox.query_json(&["--nocache", "apple \"pie\""]);
+
+    assert_eq!(result["found"], true);
+    assert_eq!(result["translations"], json!(["n. 苹果", "n. 苹果树"]));
+    assert_eq!(result["pronunciation"], "ˈæpl");
+    assert_eq!(
+        result["examples"],
+        json!([
+            ["An apple a day.", "一天一个苹果。"],
+            ["Apple pie.", "苹果派。"]
+        ])
+    );
+    assert_eq!(result["source"], json!({"Online": {"Custom": "corp-mt"}}));
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/v1/translate?q=apple%20%22pie%22&to=zh");
+    assert_eq!(request.header("Authorization"), Some("Bearer corp-token"));
+    assert_eq!(request.body_json()["text"], "apple \"pie\"");
+}
+
+#[test]
+fn test_http_template_query_with_placeholder_is_sent_verbatim() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"text": "译文"})));
+    let sandbox = Sandbox::new(&format!(
+        r#"providers = ["corp-mt"]
+
+[[http_templates]]
+name = "corp-mt"
+url = "{}/v1/translate"
+body = '{{"text": "{{{{query}}}}", "from": "{{{{from}}}}", "to": "{{{{to}}}}"}}'
+
+[http_templates.fields]
+translations = "$.text"
+"#,
+        server.url()
+    ));
+
+    sandbox.query_json(&["--nocache", "say {{to}} and {{from}}"]);
+
+    // 查询中的占位符不会被再次替换
+    let body = server.requests()[0].body_json();
+    assert_eq!(body["text"], "say {{to}} and {{from}}");
+    assert_eq!(body["to"], "zh");
+}
+
+#[test]
+fn test_http_template_quoted_key_with_dots() {
+    let server = StubServer::start(|_| {
+        StubResponse::json(json!({"result": {"zh.CN": ["苹果"], "zh": ["错误"]}}))
+    });
+    let sandbox = Sandbox::new(&format!(
+        r#"providers = ["corp-mt"]
+
+[[http_templates]]
+name = "corp-mt"
+url = "{}/translate"
+
+[http_templates.fields]
+translations = "$.result['zh.CN'][0]"
+"#,
+        server.url()
+    ));
+
+    let result = sandbox.query_json(&["--nocache", "apple"]);
+
+    assert_eq!(result["translations"], json!(["苹果"]));
+}
+
+#[test]
+fn test_http_template_malformed_paths_are_config_errors() {
+    for path in ["$.items[0]]", "$.a[0é", "$.a['b]", "$.a[]", "$.a..b"] {
+        let server = StubServer::start(|_| StubResponse::json(json!({})));
+        let sandbox = Sandbox::new(&format!(
+            r#"providers = ["corp-mt"]
+
+[[http_templates]]
+name = "corp-mt"
+url = "{}/translate"
+
+[http_templates.fields]
+translations = "{}"
+"#,
+            server.url(),
+            path
+        ));
+
+        let output = sandbox.run(&["--json", "--nocache", "hello"]);
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success(), "{} was accepted", path);
+        assert!(stderr.contains("invalid path"), "{}: {}", path, stderr);
+        assert!(!stderr.contains("panicked"), "{}: {}", path, stderr);
+        assert!(server.requests().is_empty());
+    }
+}
+
+#[test]
+fn test_disabled_template_is_not_used() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"text": "x"})));
+    let sandbox = Sandbox::new(&format!(
+        r#"providers = ["corp-mt"]
+
+[[http_templates]]
+name = "corp-mt"
+enable = false
+url = "{}/translate"
+"#,
+        server.url()
+    ));
+
+    let output = sandbox.run(&["--json", "--nocache", "hello"]);
+
+    assert!(!output.status.success());
+    assert!(server.requests().is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No online provider enabled"));
+}
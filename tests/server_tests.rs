@@ -0,0 +1,258 @@
+//! HTTP JSON 服务模式测试（kd serve）
+
+mod common;
+
+use common::{entry, Sandbox};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Stdio};
+use std::time::{Duration, Instant};
+
+struct Server {
+    child: Child,
+    addr: String,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+fn start_server(sandbox: &Sandbox) -> Server {
+    // 先占用一个空闲端口再释放，交给 kd 监听
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{}", port);
+    let child = sandbox
+        .kd()
+        .args(["serve", "--listen", &addr])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let server = Server { child, addr };
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while TcpStream::connect(&server.addr).is_err() {
+        assert!(Instant::now() < deadline, "server did not start");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    server
+}
+
+struct HttpResponse {
+    status: u16,
+    head: String,
+    body: String,
+}
+
+impl HttpResponse {
+    fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("response is not JSON")
+    }
+}
+
+fn request(server: &Server, method: &str, path: &str, headers: &[(&str, &str)]) -> HttpResponse {
+    let mut stream = TcpStream::connect(&server.addr).unwrap();
+    let mut req = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", method, path, server.addr);
+    for (k, v) in headers {
+        req.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    req.push_str("Connection: close\r\n\r\n");
+    stream.write_all(req.as_bytes()).unwrap();
+
+    let mut raw = String::new();
+    stream.read_to_string(&mut raw).unwrap();
+    let (head, body) = raw.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    HttpResponse {
+        status,
+        head: head.to_lowercase(),
+        body: body.to_string(),
+    }
+}
+
+fn get(server: &Server, path: &str) -> HttpResponse {
+    request(server, "GET", path, &[])
+}
+
+#[test]
+fn test_query_status_and_history() {
+    let sandbox = Sandbox::new("providers = []\n");
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+    let server = start_server(&sandbox);
+
+    let response = get(&server, "/query?q=hello");
+    assert_eq!(response.status, 200);
+    let result = response.json();
+    assert_eq!(result["query"], "hello");
+    assert_eq!(result["found"], true);
+    assert_eq!(result["source"], "OfflineDb");
+
+    // 与 --json 输出的结构一致
+    let cli = sandbox.query_json(&["hello"]);
+    let mut keys: Vec<_> = cli.as_object().unwrap().keys().collect();
+    let mut server_keys: Vec<_> = result.as_object().unwrap().keys().collect();
+    keys.sort();
+    server_keys.sort();
+    assert_eq!(keys, server_keys);
+
+    let status = get(&server, "/status").json();
+    assert_eq!(status["database_records"], 1);
+    assert_eq!(status["memory_cache_entries"], 1);
+
+    let history = get(&server, "/history?limit=10").json();
+    let history = history.as_array().unwrap();
+    assert!(!history.is_empty());
+    assert_eq!(history[0]["query"], "hello");
+    assert_eq!(history[0]["found"], true);
+}
+
+#[test]
+fn test_history_only_records_server_queries_up_to_limit() {
+    let sandbox = Sandbox::new("providers = []\n\n[server]\nhistory_limit = 2\n");
+    for word in ["one", "two", "three"] {
+        sandbox.seed(word, &entry(word, &["n. 数字"]));
+    }
+    // 命令行查询不写入历史
+    sandbox.query_json(&["one"]);
+    let server = start_server(&sandbox);
+
+    for word in ["one", "two", "three"] {
+        assert_eq!(get(&server, &format!("/query?q={}", word)).status, 200);
+    }
+
+    let history = get(&server, "/history").json();
+    let queries: Vec<_> = history
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["query"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(queries, ["three", "two"]);
+}
+
+#[test]
+fn test_query_errors() {
+    let sandbox = Sandbox::new("providers = []\n");
+    let server = start_server(&sandbox);
+
+    let missing = get(&server, "/query");
+    assert_eq!(missing.status, 400);
+    assert!(missing.json()["error"]
+        .as_str()
+        .unwrap()
+        .contains("Missing query"));
+
+    let unknown = get(&server, "/query?q=nothing");
+    assert_eq!(unknown.status, 503);
+    assert!(unknown.json()["error"]
+        .as_str()
+        .unwrap()
+        .contains("No online provider enabled"));
+}
+
+#[test]
+fn test_bearer_token_and_cors() {
+    let sandbox = Sandbox::new(
+        r#"providers = []
+
+[server]
+token = "s3cret"
+cors_origins = ["http://localhost:3000"]
+"#,
+    );
+    sandbox.seed("hello", &entry("hello", &["int. 你好"]));
+    let server = start_server(&sandbox);
+
+    assert_eq!(get(&server, "/status").status, 401);
+    let wrong = request(
+        &server,
+        "GET",
+        "/status",
+        &[("Authorization", "Bearer nope")],
+    );
+    assert_eq!(wrong.status, 401);
+
+    let ok = request(
+        &server,
+        "GET",
+        "/query?q=hello",
+        &[
+            ("Authorization", "Bearer s3cret"),
+            ("Origin", "http://localhost:3000"),
+        ],
+    );
+    assert_eq!(ok.status, 200);
+    assert!(ok
+        .head
+        .contains("access-control-allow-origin: http://localhost:3000"));
+
+    // 预检请求不需要令牌
+    let preflight = request(
+        &server,
+        "OPTIONS",
+        "/query?q=hello",
+        &[
+            ("Origin", "http://localhost:3000"),
+            ("Access-Control-Request-Method", "GET"),
+            ("Access-Control-Request-Headers", "authorization"),
+        ],
+    );
+    assert_eq!(preflight.status, 200);
+    assert!(preflight.head.contains("access-control-allow-headers"));
+}
+
+#[test]
+fn test_memory_cache_is_bounded() {
+    let sandbox = Sandbox::new("providers = []\n\n[cache]\nmemory_entries = 2\n");
+    for word in ["apple", "banana", "cherry"] {
+        sandbox.seed(word, &entry(word, &["释义"]));
+    }
+    let server = start_server(&sandbox);
+
+    for word in ["apple", "banana", "apple", "cherry"] {
+        get(&server, &format!("/query?q={}", word));
+    }
+    // banana 最久未使用，被淘汰；apple 仍在内存中
+    assert_eq!(
+        get(&server, "/query?q=apple").json()["source"],
+        "LocalCache"
+    );
+    assert_eq!(
+        get(&server, "/query?q=banana").json()["source"],
+        "OfflineDb"
+    );
+
+    let status = get(&server, "/status").json();
+    let cache = &status["memory_cache"];
+    assert_eq!(cache["entries"], 2);
+    assert_eq!(cache["max_entries"], 2);
+    assert_eq!(cache["hits"], 2);
+    assert_eq!(cache["misses"], 4);
+    assert_eq!(cache["evictions"], 2);
+    assert!(cache["bytes"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn test_memory_cache_size_limit() {
+    let sandbox = Sandbox::new("providers = []\n\n[cache]\nmemory_size = \"1KB\"\n");
+    let long = "释义".repeat(100);
+    for word in ["apple", "banana"] {
+        sandbox.seed(word, &entry(word, &[&long]));
+    }
+    let server = start_server(&sandbox);
+
+    get(&server, "/query?q=apple");
+    get(&server, "/query?q=banana");
+
+    let cache = &get(&server, "/status").json()["memory_cache"];
+    assert_eq!(cache["entries"], 1);
+    assert_eq!(cache["evictions"], 1);
+    assert!(cache["bytes"].as_u64().unwrap() <= 1024);
+}
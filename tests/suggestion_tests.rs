@@ -0,0 +1,65 @@
+//! 拼写建议测试（查不到时给出相近词）
+
+mod common;
+
+use common::{entry, Sandbox, StubResponse, StubServer};
+use serde_json::json;
+
+fn seed_words(sandbox: &Sandbox) {
+    sandbox.seed("apple", &entry("apple", &["n. 苹果"]));
+    sandbox.seed("ample", &entry("ample", &["adj. 充足的"]));
+    sandbox.seed("apply", &entry("apply", &["v. 申请；应用"]));
+    sandbox.seed("believe", &entry("believe", &["v. 相信"]));
+}
+
+#[test]
+fn test_suggestions_when_online_unavailable() {
+    let sandbox = Sandbox::new("providers = []\n");
+    seed_words(&sandbox);
+
+    let result = sandbox.query_json(&["aple"]);
+    assert_eq!(result["found"], false);
+    assert_eq!(result["source"], "Suggestions");
+    assert_eq!(result["suggestions"], json!(["ample", "apple"]));
+
+    // 较长的词允许两处拼写错误
+    let result = sandbox.query_json(&["beleive"]);
+    assert_eq!(result["suggestions"], json!(["believe"]));
+
+    let output = sandbox.run(&["aple"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[建议]"), "stdout: {}", stdout);
+    assert!(stdout.contains("您是不是要找"), "stdout: {}", stdout);
+    assert!(stdout.contains("1. ample"));
+    assert!(stdout.contains("2. apple"));
+}
+
+#[test]
+fn test_error_kept_without_suggestions() {
+    let sandbox = Sandbox::new("providers = []\n");
+    seed_words(&sandbox);
+
+    let output = sandbox.run(&["zzzzzz"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No online provider enabled"));
+
+    let sandbox = Sandbox::new("providers = []\nmax_suggestions = 0\n");
+    seed_words(&sandbox);
+    assert!(!sandbox.run(&["aple"]).status.success());
+}
+
+#[test]
+fn test_suggestions_when_online_finds_nothing() {
+    let server = StubServer::start(|_| StubResponse::json(json!({"value": []})));
+    let sandbox = Sandbox::new(&format!(
+        "providers = [\"bing\"]\n\n[bing]\nendpoint = \"{}\"\n",
+        server.url()
+    ));
+    seed_words(&sandbox);
+
+    let result = sandbox.query_json(&["appel"]);
+    assert_eq!(result["found"], false);
+    assert_eq!(result["suggestions"][0], "apple");
+    assert!(!server.requests().is_empty());
+}
@@ -0,0 +1,649 @@
+//! 离线词库更新测试（--update-dict 从本地桩服务器下载并校验词库）
+
+mod common;
+
+use common::{Sandbox, StubResponse, StubServer};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::Path;
+
+const SECRET_KEY: [u8; 32] = [7; 32];
+
+/// 构造旧版词库：en 表中保存 zlib 压缩的旧格式 JSON
+fn legacy_db(db_path: &Path, words: &[(&str, &str)]) {
+    let conn = rusqlite::Connection::open(db_path).unwrap();
+    conn.execute("CREATE TABLE en (query TEXT, detail BLOB)", [])
+        .unwrap();
+    for (word, meaning) in words {
+        let legacy = json!({"k": word, "para": [meaning]});
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(legacy.to_string().as_bytes()).unwrap();
+        conn.execute(
+            "INSERT INTO en (query, detail) VALUES (?1, ?2)",
+            rusqlite::params![word, encoder.finish().unwrap()],
+        )
+        .unwrap();
+    }
+}
+
+/// 构造包含旧版词库的压缩包
+fn legacy_archive(words: &[(&str, &str)]) -> Vec<u8> {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("legacy.db");
+    legacy_db(&db_path, words);
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("legacy.db", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(&std::fs::read(&db_path).unwrap()).unwrap();
+    zip.finish().unwrap().into_inner()
+}
+
+/// 描述压缩包的清单，可选用测试密钥签名
+fn manifest(archive: &[u8], signed: bool) -> serde_json::Value {
+    let sha256 = hex::encode(Sha256::digest(archive));
+    let mut manifest = json!({"version": "2024.05", "size": archive.len(), "sha256": sha256});
+    if signed {
+        let message = format!("2024.05\n{}\n{}", archive.len(), sha256);
+        let signature = SigningKey::from_bytes(&SECRET_KEY).sign(message.as_bytes());
+        manifest["signature"] = json!(hex::encode(signature.to_bytes()));
+    }
+    manifest
+}
+
+/// 提供压缩包及其清单的桩服务器（manifest 为 None 时清单返回 404）
+fn serve(archive: Vec<u8>, manifest: Option<serde_json::Value>) -> StubServer {
+    StubServer::start(move |request| match request.path.as_str() {
+        "/kd_data.zip" => StubResponse::bytes(archive.clone()),
+        "/kd_data.zip.manifest.json" => match &manifest {
+            Some(manifest) => StubResponse::json(manifest.clone()),
+            None => StubResponse::status(404),
+        },
+        _ => StubResponse::status(404),
+    })
+}
+
+/// 以桩服务器为唯一镜像更新词库的运行环境，extra 为 [update] 中的其他配置
+fn sandbox(server: &StubServer, extra: &str) -> Sandbox {
+    Sandbox::new(&format!(
+        "providers = []\n\n[update]\nmirrors = [{{ url = \"{}/kd_data.zip\" }}]\n{}",
+        server.url(),
+        extra
+    ))
+}
+
+fn signed() -> String {
+    format!("public_key = \"{}\"\n", public_key())
+}
+
+fn public_key() -> String {
+    hex::encode(
+        SigningKey::from_bytes(&SECRET_KEY)
+            .verifying_key()
+            .to_bytes(),
+    )
+}
+
+#[test]
+fn test_verified_archive_is_installed() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let server = serve(archive.clone(), Some(manifest(&archive, false)));
+    let sandbox = sandbox(&server, "");
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("version 2024.05"));
+    let result = sandbox.query_json(&["apple"]);
+    assert_eq!(result["translations"], json!(["n. 苹果"]));
+    assert!(!sandbox.kd_dir().join("kd_data.zip").exists());
+}
+
+#[test]
+fn test_tampered_archive_is_rejected() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let listed = manifest(&archive, false);
+    // 大小不变，只改动其中一个字节
+    let mut tampered = archive;
+    let middle = tampered.len() / 2;
+    tampered[middle] ^= 0xff;
+    let server = serve(tampered, Some(listed));
+    let sandbox = sandbox(&server, "");
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Integrity check failed"), "{}", stderr);
+    assert!(stderr.contains("SHA-256"), "{}", stderr);
+    // 校验失败的压缩包被删除，也没有写入任何词条
+    assert!(!sandbox.kd_dir().join("kd_data.zip").exists());
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM cache", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_signed_manifest_is_checked() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let server = serve(archive.clone(), Some(manifest(&archive, true)));
+    let sandbox = sandbox(&server, &signed());
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+#[test]
+fn test_bad_or_missing_signature_is_rejected() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let mut forged = manifest(&archive, true);
+    forged["version"] = json!("2099.01");
+    let cases = [
+        (Some(forged), "signature does not match"),
+        (Some(manifest(&archive, false)), "manifest is not signed"),
+        (None, "no manifest published"),
+    ];
+
+    for (listed, message) in cases {
+        let server = serve(archive.clone(), listed);
+        let sandbox = sandbox(&server, &signed());
+
+        let output = sandbox.run(&["--update-dict"]);
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(message), "{}", stderr);
+        // 清单未通过校验时不会下载压缩包
+        assert!(server
+            .requests()
+            .iter()
+            .all(|request| request.path != "/kd_data.zip"));
+    }
+}
+
+#[test]
+fn test_archive_without_manifest_is_installed_with_warning() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let server = serve(archive.clone(), None);
+    // 默认配置（未设置 public_key），与默认镜像一样没有发布清单
+    let sandbox = sandbox(&server, "");
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("can't be verified"));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+/// 文件版本为 etag 的下载源：完整请求只返回前一半内容后断开，
+/// If-Range 与 etag 一致的 Range 请求返回剩余部分，不一致时重新返回完整请求的内容
+fn serve_interrupted(archive: Vec<u8>, etag: &'static str) -> StubServer {
+    StubServer::start(move |request| {
+        if request.path != "/kd_data.zip" {
+            return StubResponse::status(404);
+        }
+        let range = request
+            .header("Range")
+            .filter(|_| request.header("If-Range") == Some(etag));
+        let mut response = match range {
+            None => {
+                let mut response = StubResponse::bytes(archive[..archive.len() / 2].to_vec());
+                response
+                    .headers
+                    .push(("Content-Length".to_string(), archive.len().to_string()));
+                response
+            }
+            Some(range) => {
+                let start: usize = range
+                    .trim_start_matches("bytes=")
+                    .trim_end_matches('-')
+                    .parse()
+                    .unwrap();
+                let mut response = StubResponse::bytes(archive[start..].to_vec());
+                response.status = 206;
+                response.headers.push((
+                    "Content-Range".to_string(),
+                    format!("bytes {}-{}/{}", start, archive.len() - 1, archive.len()),
+                ));
+                response
+            }
+        };
+        response
+            .headers
+            .push(("ETag".to_string(), etag.to_string()));
+        response
+    })
+}
+
+#[test]
+fn test_interrupted_download_is_resumed() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let half = archive.len() / 2;
+    let server = serve_interrupted(archive, "\"v1\"");
+    let sandbox = sandbox(&server, "");
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("retrying in 1s"));
+    let ranges: Vec<_> = server
+        .requests()
+        .iter()
+        .filter_map(|request| request.header("Range").map(str::to_string))
+        .collect();
+    assert_eq!(ranges, vec![format!("bytes={}-", half)]);
+    let resumed = server
+        .requests()
+        .into_iter()
+        .find(|request| request.header("Range").is_some())
+        .unwrap();
+    assert_eq!(resumed.header("If-Range"), Some("\"v1\""));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+#[test]
+fn test_partial_download_is_kept_for_next_run() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let server = serve_interrupted(archive, "\"v1\"");
+    let sandbox = sandbox(&server, "retries = 0\n");
+    let zip_path = sandbox.kd_dir().join("kd_data.zip");
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    // 中断的下载只留下 .part 文件，不会被当作完整的压缩包
+    assert!(!output.status.success());
+    assert!(!zip_path.exists());
+    assert!(sandbox.kd_dir().join("kd_data.zip.part").exists());
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Resuming download"));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+/// 在数据目录中留下一个未完成的下载及其版本标识
+fn leave_part(sandbox: &Sandbox, bytes: &[u8], validator: &str) {
+    std::fs::write(sandbox.kd_dir().join("kd_data.zip.part"), bytes).unwrap();
+    std::fs::write(
+        sandbox.kd_dir().join("kd_data.zip.part.validator"),
+        validator,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_part_of_changed_file_is_downloaded_again() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let server = serve_interrupted(archive, "\"v2\"");
+    let sandbox = sandbox(&server, "");
+    leave_part(&sandbox, b"part of the old archive", "\"v1\"");
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let first = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path == "/kd_data.zip")
+        .unwrap();
+    assert_eq!(first.header("If-Range"), Some("\"v1\""));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+#[test]
+fn test_misaligned_content_range_restarts_download() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    // 忽略 Range 的起点，总是从头返回
+    let server = StubServer::start({
+        let archive = archive.clone();
+        move |request| {
+            if request.path != "/kd_data.zip" {
+                return StubResponse::status(404);
+            }
+            let mut response = StubResponse::bytes(archive.clone());
+            if request.header("Range").is_some() {
+                response.status = 206;
+                response.headers.push((
+                    "Content-Range".to_string(),
+                    format!("bytes 0-{}/{}", archive.len() - 1, archive.len()),
+                ));
+            }
+            response
+                .headers
+                .push(("ETag".to_string(), "\"v1\"".to_string()));
+            response
+        }
+    });
+    let sandbox = sandbox(&server, "");
+    leave_part(&sandbox, &archive[..10], "\"v1\"");
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("starting over"));
+    assert!(server.requests().last().unwrap().header("Range").is_none());
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+#[test]
+fn test_truncated_zip_is_downloaded_again() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let server = serve(archive.clone(), None);
+    let sandbox = sandbox(&server, "");
+    std::fs::write(
+        sandbox.kd_dir().join("kd_data.zip"),
+        &archive[..archive.len() / 2],
+    )
+    .unwrap();
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("downloading again"));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+/// 按给定的镜像列表更新词库的运行环境
+fn sandbox_with_mirrors(urls: &[String], selection: &str) -> Sandbox {
+    let mirrors: Vec<_> = urls
+        .iter()
+        .map(|url| format!("{{ url = \"{}\" }}", url))
+        .collect();
+    Sandbox::new(&format!(
+        "providers = []\n\n[update]\nmirrors = [{}]\nselection = \"{}\"\n",
+        mirrors.join(", "),
+        selection
+    ))
+}
+
+#[test]
+fn test_falls_back_to_next_mirror() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let broken = StubServer::start(|_| StubResponse::status(404));
+    let server = serve(archive.clone(), Some(manifest(&archive, false)));
+    let sandbox = sandbox_with_mirrors(
+        &[
+            format!("{}/kd_data.zip", broken.url()),
+            format!("{}/kd_data.zip", server.url()),
+        ],
+        "order",
+    );
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("failed"));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+#[test]
+fn test_latency_selection_skips_unreachable_mirror() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let server = serve(archive.clone(), Some(manifest(&archive, false)));
+    let missing = StubServer::start(|_| StubResponse::status(404));
+    let sandbox = sandbox_with_mirrors(
+        &[
+            "http://127.0.0.1:1/kd_data.zip".to_string(),
+            format!("{}/kd_data.zip", missing.url()),
+            format!("{}/kd_data.zip", server.url()),
+        ],
+        "latency",
+    );
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // 可达且有文件的镜像排在前面，无需先尝试不可达或返回 404 的镜像
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("failed"));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+#[test]
+fn test_legacy_url_is_used_as_only_mirror() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let server = serve(archive.clone(), Some(manifest(&archive, false)));
+    let sandbox = Sandbox::new(&format!(
+        "providers = []\n\n[update]\nurl = \"{}/kd_data.zip\"\n",
+        server.url()
+    ));
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(server
+        .requests()
+        .iter()
+        .any(|request| request.path == "/kd_data.zip"));
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+#[test]
+fn test_file_mirror() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let mirror_dir = tempfile::tempdir().unwrap();
+    let archive_path = mirror_dir.path().join("kd_data.zip");
+    std::fs::write(&archive_path, &archive).unwrap();
+    std::fs::write(
+        mirror_dir.path().join("kd_data.zip.manifest.json"),
+        manifest(&archive, false).to_string(),
+    )
+    .unwrap();
+    let url = format!("file://{}", archive_path.display());
+    let sandbox = sandbox_with_mirrors(&[url], "latency");
+
+    let output = sandbox.run(&["--update-dict"]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("version 2024.05"));
+    // 源文件保持不变
+    assert!(archive_path.exists());
+    assert_eq!(sandbox.query_json(&["apple"])["found"], true);
+}
+
+/// 只配置了不可达镜像的运行环境，用于确认离线安装不访问网络
+fn offline_sandbox() -> Sandbox {
+    sandbox_with_mirrors(&["http://127.0.0.1:1/kd_data.zip".to_string()], "order")
+}
+
+fn assert_installed(sandbox: &Sandbox, output: &std::process::Output) {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // 没有清单的本地文件直接安装，不提示无法校验
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("can't be verified"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Dictionary update complete"));
+    let result = sandbox.query_json(&["apple"]);
+    assert_eq!(result["translations"], json!(["n. 苹果"]));
+}
+
+#[test]
+fn test_install_from_local_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let archive_path = dir.path().join("kd_data.zip");
+    std::fs::write(&archive_path, legacy_archive(&[("apple", "n. 苹果")])).unwrap();
+    let sandbox = offline_sandbox();
+
+    let output = sandbox.run(&["update-dict", "--from", archive_path.to_str().unwrap()]);
+
+    assert_installed(&sandbox, &output);
+    // 用户提供的压缩包保持不变
+    assert!(archive_path.exists());
+}
+
+#[test]
+fn test_install_from_legacy_db() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("kd_legacy.db");
+    legacy_db(&db_path, &[("apple", "n. 苹果")]);
+    let sandbox = offline_sandbox();
+
+    let output = sandbox.run(&["update-dict", "--from", db_path.to_str().unwrap()]);
+
+    assert_installed(&sandbox, &output);
+    assert!(db_path.exists());
+}
+
+#[test]
+fn test_install_from_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("kd_data.zip"),
+        legacy_archive(&[("apple", "n. 苹果")]),
+    )
+    .unwrap();
+    let sandbox = offline_sandbox();
+
+    let output = sandbox.run(&["update-dict", "--from", dir.path().to_str().unwrap()]);
+
+    assert_installed(&sandbox, &output);
+}
+
+#[test]
+fn test_local_files_need_manifest_when_signature_required() {
+    let dir = tempfile::tempdir().unwrap();
+    let archive_path = dir.path().join("kd_data.zip");
+    std::fs::write(&archive_path, legacy_archive(&[("apple", "n. 苹果")])).unwrap();
+    let db_path = dir.path().join("kd_legacy.db");
+    legacy_db(&db_path, &[("apple", "n. 苹果")]);
+    let sandbox = Sandbox::new(&format!(
+        "providers = []\n\n[update]\nmirrors = [{{ url = \"http://127.0.0.1:1/kd_data.zip\" }}]\n{}",
+        signed()
+    ));
+
+    for path in [&archive_path, &db_path] {
+        let output = sandbox.run(&["update-dict", "--from", path.to_str().unwrap()]);
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("no manifest published"), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_local_archive_is_checked_against_manifest() {
+    let archive = legacy_archive(&[("apple", "n. 苹果")]);
+    let dir = tempfile::tempdir().unwrap();
+    let archive_path = dir.path().join("kd_data.zip");
+    std::fs::write(&archive_path, &archive[..archive.len() - 1]).unwrap();
+    std::fs::write(
+        dir.path().join("kd_data.zip.manifest.json"),
+        manifest(&archive, false).to_string(),
+    )
+    .unwrap();
+    let sandbox = offline_sandbox();
+
+    let output = sandbox.run(&["update-dict", "--from", archive_path.to_str().unwrap()]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Integrity check failed"));
+}
+
+#[test]
+fn test_migration_streams_large_tables() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("kd_legacy.db");
+    let words: Vec<_> = (0..2500).map(|i| format!("word{}", i)).collect();
+    let pairs: Vec<_> = words.iter().map(|w| (w.as_str(), "n. 示例")).collect();
+    legacy_db(&db_path, &pairs);
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+    // 无法解析的词条计为错误，不影响其他词条
+    conn.execute(
+        "INSERT INTO en (query, detail) VALUES ('broken', x'00ff')",
+        [],
+    )
+    .unwrap();
+    conn.execute("CREATE TABLE ch (query TEXT, detail BLOB)", [])
+        .unwrap();
+    conn.execute(
+        "INSERT INTO ch (query, detail) VALUES ('苹果', ?1)",
+        [json!({"k": "苹果", "para": ["apple"]})
+            .to_string()
+            .into_bytes()],
+    )
+    .unwrap();
+    drop(conn);
+    let sandbox = offline_sandbox();
+
+    let output = sandbox.run(&["update-dict", "--from", db_path.to_str().unwrap()]);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Table en completed: 2500 records inserted, 1 errors"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("records/s"));
+    let conn = rusqlite::Connection::open(sandbox.db_path()).unwrap();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM cache", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 2501);
+    assert_eq!(sandbox.query_json(&["word2499"])["found"], true);
+    assert_eq!(
+        sandbox.query_json(&["苹果"])["translations"],
+        json!(["apple"])
+    );
+}